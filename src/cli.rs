@@ -0,0 +1,267 @@
+use squirrel_browser_automation::config;
+use squirrel_browser_automation::wf::export::ExportFormat;
+
+/// `Cli` struct defines the command line interface of Squirrel.
+#[derive(clap::Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// `Command` enum defines the supported subcommands.
+#[derive(clap::Subcommand, Debug)]
+pub enum Command {
+    /// Run the given workflow.
+    Run(RunArgs),
+    /// Check the given workflow (such as the element-stack discipline of its
+    /// steps), without starting the browser.
+    Validate {
+        /// Path of the workflow (.yaml) file.
+        workflow_file_path: String,
+    },
+    /// List all the supported workflow steps.
+    ListSteps,
+    /// Explain the given workflow step (such as `PageLocateElements`).
+    Explain {
+        /// Name of the workflow step.
+        step: String,
+    },
+    /// Print the JSON Schema of workflow files (such as for the editors
+    /// using yaml-language-server).
+    Schema,
+    /// Inspect the configurations.
+    Config {
+        #[command(subcommand)]
+        command: ConfigSubcommand,
+    },
+}
+
+/// `RunArgs` struct defines the arguments of the `run` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct RunArgs {
+    /// Path of the workflow (.yaml) file.
+    pub workflow_file_path: String,
+    /// Workflow variable (such as `--var term=rust`), overriding the one of
+    /// the same name within the `vars` of the workflow (can be repeated)
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_var)]
+    pub vars: Vec<(String, String)>,
+    #[command(flatten)]
+    pub config_args: ConfigArgs,
+}
+
+/// Parse a workflow variable given as `key=value`.
+fn parse_var(var: &str) -> Result<(String, String), String> {
+    match var.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("invalid variable {var:?} (expected KEY=VALUE)")),
+    }
+}
+
+/// `ConfigSubcommand` enum defines the subcommands of the `config` subcommand.
+#[derive(clap::Subcommand, Debug)]
+pub enum ConfigSubcommand {
+    /// Print the resolved configurations, and the layer each of them came from.
+    Show(ConfigArgs),
+}
+
+/// `ConfigArgs` struct defines one named flag for each `Config` field.
+/// The flags take precedence over the configuration file and the
+/// `SQUIRREL_*` environment variables, which in turn take precedence
+/// over the defaults.
+#[derive(clap::Args, Debug)]
+pub struct ConfigArgs {
+    /// Path of the configuration (.toml/.yaml) file [default: first found
+    /// out of squirrel.toml, squirrel.yaml and squirrel.yml]
+    #[arg(long)]
+    pub config: Option<String>,
+    /// URL of the WebDriver server [default: http://localhost:9515]
+    #[arg(long)]
+    pub webdriver_url: Option<String>,
+    /// Whether to run the browser in headless mode [default: true]
+    #[arg(long)]
+    pub headless_browser: Option<bool>,
+    /// Whitespace separated browser args (such as "--no-sandbox --disable-gpu")
+    #[arg(long, allow_hyphen_values = true)]
+    pub browser_args: Option<String>,
+    /// Directory where screenshots and other files are written [default: temp/]
+    #[arg(long)]
+    pub temp_dir: Option<String>,
+    /// Width of the browser window [default: 1200]
+    #[arg(long)]
+    pub window_width: Option<u32>,
+    /// Height of the browser window [default: 1293]
+    #[arg(long)]
+    pub window_height: Option<u32>,
+    /// Factor by which `PageWait` durations are scaled in headless mode [default: 0.1]
+    #[arg(long)]
+    pub remote_wait_factor: Option<f64>,
+    /// Whether to print debugging information [default: false]
+    #[arg(long)]
+    pub debug_mode: Option<bool>,
+    /// Format ("json", "ndjson" or "csv") to export the saved values in, to
+    /// a file under `temp_dir` at the end of the run [default: none]
+    #[arg(long)]
+    pub export_format: Option<ExportFormat>,
+}
+
+impl ConfigArgs {
+    /// Split the arguments into the path of the configuration file, and the
+    /// command line configuration layer.
+    pub fn into_layer(self) -> (Option<String>, config::ConfigLayer) {
+        let layer = config::ConfigLayer {
+            webdriver_url: self.webdriver_url,
+            headless_browser: self.headless_browser,
+            browser_args: self.browser_args,
+            temp_dir: self.temp_dir,
+            window_width: self.window_width,
+            window_height: self.window_height,
+            remote_wait_factor: self.remote_wait_factor,
+            debug_mode: self.debug_mode,
+            export_format: self.export_format,
+        };
+        (self.config, layer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn parse_run_args(args: &[&str]) -> (String, Option<String>, config::ConfigLayer) {
+        match Cli::try_parse_from(args)
+            .expect("Failed to parse arguments")
+            .command
+        {
+            Command::Run(run_args) => {
+                let (config_file, layer) = run_args.config_args.into_layer();
+                (run_args.workflow_file_path, config_file, layer)
+            }
+            command => panic!("Unexpected command: {:?}", command),
+        }
+    }
+
+    #[test]
+    fn run_with_missing_workflow_path() {
+        let result = Cli::try_parse_from(["squirrel", "run"]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn run_with_workflow_path() {
+        let (workflow_file_path, config_file, layer) =
+            parse_run_args(&["squirrel", "run", "./sample_workflow.yaml"]);
+
+        assert_eq!(workflow_file_path, "./sample_workflow.yaml");
+        assert_eq!(config_file, None);
+        assert_eq!(layer.headless_browser, None);
+        assert_eq!(layer.webdriver_url, None);
+        assert_eq!(layer.temp_dir, None);
+        assert_eq!(layer.window_width, None);
+        assert_eq!(layer.debug_mode, None);
+    }
+
+    #[test]
+    fn run_with_all_flags() {
+        let (workflow_file_path, config_file, layer) = parse_run_args(&[
+            "squirrel",
+            "run",
+            "./sample_workflow.yaml",
+            "--config",
+            "ci.toml",
+            "--webdriver-url",
+            "http://localhost:4444",
+            "--headless-browser",
+            "false",
+            "--browser-args",
+            "--no-sandbox --disable-gpu",
+            "--temp-dir",
+            "output/",
+            "--window-width",
+            "800",
+            "--window-height",
+            "600",
+            "--remote-wait-factor",
+            "0.5",
+            "--debug-mode",
+            "true",
+            "--export-format",
+            "csv",
+        ]);
+
+        assert_eq!(workflow_file_path, "./sample_workflow.yaml");
+        assert_eq!(config_file, Some("ci.toml".to_string()));
+        assert_eq!(
+            layer.webdriver_url,
+            Some("http://localhost:4444".to_string())
+        );
+        assert_eq!(layer.headless_browser, Some(false));
+        assert_eq!(
+            layer.browser_args,
+            Some("--no-sandbox --disable-gpu".to_string())
+        );
+        assert_eq!(layer.temp_dir, Some("output/".to_string()));
+        assert_eq!(layer.window_width, Some(800));
+        assert_eq!(layer.window_height, Some(600));
+        assert_eq!(layer.remote_wait_factor, Some(0.5));
+        assert_eq!(layer.debug_mode, Some(true));
+        assert_eq!(layer.export_format, Some(ExportFormat::Csv));
+    }
+
+    #[test]
+    fn run_with_vars() {
+        let cli = Cli::try_parse_from([
+            "squirrel",
+            "run",
+            "./sample_workflow.yaml",
+            "--var",
+            "term=rust",
+            "--var",
+            "url=https://example.com/?a=b",
+        ])
+        .expect("Failed to parse arguments");
+
+        match cli.command {
+            Command::Run(run_args) => assert_eq!(
+                run_args.vars,
+                vec![
+                    ("term".to_string(), "rust".to_string()),
+                    ("url".to_string(), "https://example.com/?a=b".to_string()),
+                ]
+            ),
+            command => panic!("Unexpected command: {:?}", command),
+        }
+        let result = Cli::try_parse_from(["squirrel", "run", "./w.yaml", "--var", "term"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn run_with_invalid_headless_browser() {
+        let result = Cli::try_parse_from([
+            "squirrel",
+            "run",
+            "./sample_workflow.yaml",
+            "--headless-browser",
+            "maybe",
+        ]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn config_show_with_flags() {
+        let cli = Cli::try_parse_from(["squirrel", "config", "show", "--temp-dir", "output/"])
+            .expect("Failed to parse arguments");
+
+        match cli.command {
+            Command::Config {
+                command: ConfigSubcommand::Show(config_args),
+            } => assert_eq!(config_args.temp_dir, Some("output/".to_string())),
+            command => panic!("Unexpected command: {:?}", command),
+        }
+    }
+}
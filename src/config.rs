@@ -1,12 +1,26 @@
+use crate::error::{SquirrelError, StepPath};
+use crate::wf::export::ExportFormat;
+use std::collections::BTreeMap;
+use std::fmt;
+
 /// `Config` struct providing all passed and environmental
 /// configurations required for the program run.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Config {
     pub workflow_file_path: String,
     pub webdriver_url: String,
     pub headless_browser: bool,
     pub browser_args: Vec<String>,
     pub temp_dir: String,
+    pub window_width: u32,
+    pub window_height: u32,
+    pub remote_wait_factor: f64,
+    pub debug_mode: bool,
+    /// Format to export the saved values in at the end of the run (if any).
+    pub export_format: Option<ExportFormat>,
+    /// Workflow variables overriding the `vars` of the workflow (such as
+    /// given by `--var`).
+    pub vars: BTreeMap<String, String>,
 }
 
 pub const WEBDRIVER_URL_DEFAULT: &str = "http://localhost:9515";
@@ -17,38 +31,250 @@ pub const TEMP_DIR_DEFAULT: &str = "temp/";
 pub const TAB_SIZE: usize = 4;
 pub const WINDOW_WIDTH_DEFAULT: u32 = 1200;
 pub const WINDOW_HEIGHT_DEFAULT: u32 = 1293;
-pub const REMOTE_WAIT_FACTOR: f64 = 0.1;
-pub const DEBUG_MODE: bool = false;
-/// Parse args to construct and return a Config.
-pub fn parse_args(args: &Vec<String>) -> Config {
-    let workflow_file_path = match args.get(1) {
-        None => panic!("Provide configuration file (.yaml) path as the first argument!"),
-        Some(v) => v.clone(),
-    };
-    let webdriver_url = match args.get(2) {
-        None => WEBDRIVER_URL_DEFAULT.to_string(),
-        Some(v) => v.clone(),
-    };
-    let headless_browser = match args.get(3) {
-        None => HEADLESS_BROWSER_DEFAULT,
-        Some(elem) => elem.trim().parse().unwrap_or(HEADLESS_BROWSER_DEFAULT),
+pub const REMOTE_WAIT_FACTOR_DEFAULT: f64 = 0.1;
+pub const DEBUG_MODE_DEFAULT: bool = false;
+/// `CONFIG_FILE_NAMES` are looked up (in order) in the current directory,
+/// when no configuration file is given explicitly.
+pub const CONFIG_FILE_NAMES: [&str; 3] = ["squirrel.toml", "squirrel.yaml", "squirrel.yml"];
+/// `ENV_PREFIX` prefixes environment variables overriding configurations,
+/// such as `SQUIRREL_WEBDRIVER_URL`.
+pub const ENV_PREFIX: &str = "SQUIRREL_";
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            workflow_file_path: String::new(),
+            webdriver_url: WEBDRIVER_URL_DEFAULT.to_string(),
+            headless_browser: HEADLESS_BROWSER_DEFAULT,
+            browser_args: split_browser_args(BROWSER_ARGS_DEFAULT),
+            temp_dir: TEMP_DIR_DEFAULT.to_string(),
+            window_width: WINDOW_WIDTH_DEFAULT,
+            window_height: WINDOW_HEIGHT_DEFAULT,
+            remote_wait_factor: REMOTE_WAIT_FACTOR_DEFAULT,
+            debug_mode: DEBUG_MODE_DEFAULT,
+            export_format: None,
+            vars: BTreeMap::new(),
+        }
+    }
+}
+
+/// `ConfigLayer` struct holds configurations provided by a single source
+/// (such as a configuration file), where `None` means "not provided".
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigLayer {
+    pub webdriver_url: Option<String>,
+    pub headless_browser: Option<bool>,
+    pub browser_args: Option<String>,
+    pub temp_dir: Option<String>,
+    pub window_width: Option<u32>,
+    pub window_height: Option<u32>,
+    pub remote_wait_factor: Option<f64>,
+    pub debug_mode: Option<bool>,
+    pub export_format: Option<ExportFormat>,
+}
+
+/// `ConfigSource` enum defines the layer a configuration value came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    Default,
+    File(String),
+    Env,
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::File(path) => write!(f, "file ({path})"),
+            ConfigSource::Env => write!(f, "environment"),
+            ConfigSource::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// `ConfigValue` struct describes a resolved configuration value,
+/// along with the layer it came from.
+#[derive(Debug)]
+pub struct ConfigValue {
+    pub name: &'static str,
+    pub value: String,
+    pub source: ConfigSource,
+}
+
+impl ConfigLayer {
+    /// Read configuration layer from given `.toml` or `.yaml` file.
+    pub fn from_file(path: &str) -> Result<ConfigLayer, SquirrelError> {
+        let content = std::fs::read_to_string(path).map_err(|error| {
+            let message = format!("Failed reading configuration file {path}: {error}");
+            std::io::Error::new(error.kind(), message)
+        })?;
+        let layer = if path.ends_with(".toml") {
+            toml::from_str(&content).map_err(|error| format!("{error}"))
+        } else {
+            serde_yaml::from_str(&content).map_err(|error| format!("{error}"))
+        };
+        layer.map_err(|error| SquirrelError::Parse {
+            path: StepPath::default(),
+            message: format!("Failed parsing configuration file {path}: {error}"),
+        })
+    }
+
+    /// Construct configuration layer from given environment variables,
+    /// considering only the ones prefixed with `ENV_PREFIX`.
+    pub fn from_env(
+        vars: impl Iterator<Item = (String, String)>,
+    ) -> Result<ConfigLayer, SquirrelError> {
+        let mut layer = ConfigLayer::default();
+        for (key, value) in vars {
+            let name = match key.strip_prefix(ENV_PREFIX) {
+                Some(name) => name.to_ascii_lowercase(),
+                None => continue,
+            };
+            match name.as_str() {
+                "webdriver_url" => layer.webdriver_url = Some(value),
+                "headless_browser" => layer.headless_browser = Some(parse_env(&key, &value)?),
+                "browser_args" => layer.browser_args = Some(value),
+                "temp_dir" => layer.temp_dir = Some(value),
+                "window_width" => layer.window_width = Some(parse_env(&key, &value)?),
+                "window_height" => layer.window_height = Some(parse_env(&key, &value)?),
+                "remote_wait_factor" => layer.remote_wait_factor = Some(parse_env(&key, &value)?),
+                "debug_mode" => layer.debug_mode = Some(parse_env(&key, &value)?),
+                "export_format" => layer.export_format = Some(parse_env(&key, &value)?),
+                // not a configuration, such as `SQUIRREL_HOME`
+                _ => {}
+            }
+        }
+        Ok(layer)
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, SquirrelError>
+where
+    T::Err: fmt::Display,
+{
+    value.trim().parse().map_err(|error| SquirrelError::Parse {
+        path: StepPath::default(),
+        message: format!("Invalid value {value:?} for environment variable {key}: {error}"),
+    })
+}
+
+/// Return the configuration layers in their order of precedence (lowest
+/// first): configuration file (given one, or else the first one found out
+/// of `CONFIG_FILE_NAMES`), environment variables, and command line.
+pub fn load_layers(
+    config_file: Option<&str>,
+    cli: ConfigLayer,
+) -> Result<Vec<(ConfigSource, ConfigLayer)>, SquirrelError> {
+    let mut layers = Vec::new();
+    let config_file = match config_file {
+        Some(path) => Some(path.to_string()),
+        None => CONFIG_FILE_NAMES
+            .iter()
+            .find(|path| std::path::Path::new(path).is_file())
+            .map(|path| path.to_string()),
     };
-    let browser_args = args
-        .get(4)
-        .unwrap_or(&BROWSER_ARGS_DEFAULT.to_string())
-        .clone();
-    let browser_args = browser_args
+    if let Some(path) = config_file {
+        let layer = ConfigLayer::from_file(&path)?;
+        layers.push((ConfigSource::File(path), layer));
+    }
+    layers.push((ConfigSource::Env, ConfigLayer::from_env(std::env::vars())?));
+    layers.push((ConfigSource::Cli, cli));
+    Ok(layers)
+}
+
+/// Return the value provided by the highest precedence layer, or else the default.
+fn resolve_value<T: Clone>(
+    layers: &[(ConfigSource, ConfigLayer)],
+    default: T,
+    get: impl Fn(&ConfigLayer) -> &Option<T>,
+) -> (T, ConfigSource) {
+    layers
+        .iter()
+        .rev()
+        .find_map(|(source, layer)| get(layer).clone().map(|value| (value, source.clone())))
+        .unwrap_or((default, ConfigSource::Default))
+}
+
+impl Config {
+    /// Construct Config by resolving given layers over the defaults, and
+    /// also return the resolved values along with the layers they came from.
+    pub fn from_layers(
+        workflow_file_path: String,
+        layers: &[(ConfigSource, ConfigLayer)],
+    ) -> (Config, Vec<ConfigValue>) {
+        let mut values = Vec::new();
+        let mut record = |name: &'static str, value: String, source: ConfigSource| {
+            values.push(ConfigValue {
+                name,
+                value,
+                source,
+            })
+        };
+
+        let (webdriver_url, source) =
+            resolve_value(layers, WEBDRIVER_URL_DEFAULT.to_string(), |l| {
+                &l.webdriver_url
+            });
+        record("webdriver_url", webdriver_url.clone(), source);
+        let (headless_browser, source) =
+            resolve_value(layers, HEADLESS_BROWSER_DEFAULT, |l| &l.headless_browser);
+        record("headless_browser", headless_browser.to_string(), source);
+        let (browser_args, source) = resolve_value(layers, BROWSER_ARGS_DEFAULT.to_string(), |l| {
+            &l.browser_args
+        });
+        record("browser_args", browser_args.clone(), source);
+        let (temp_dir, source) =
+            resolve_value(layers, TEMP_DIR_DEFAULT.to_string(), |l| &l.temp_dir);
+        record("temp_dir", temp_dir.clone(), source);
+        let (window_width, source) =
+            resolve_value(layers, WINDOW_WIDTH_DEFAULT, |l| &l.window_width);
+        record("window_width", window_width.to_string(), source);
+        let (window_height, source) =
+            resolve_value(layers, WINDOW_HEIGHT_DEFAULT, |l| &l.window_height);
+        record("window_height", window_height.to_string(), source);
+        let (remote_wait_factor, source) = resolve_value(layers, REMOTE_WAIT_FACTOR_DEFAULT, |l| {
+            &l.remote_wait_factor
+        });
+        record("remote_wait_factor", remote_wait_factor.to_string(), source);
+        let (debug_mode, source) = resolve_value(layers, DEBUG_MODE_DEFAULT, |l| &l.debug_mode);
+        record("debug_mode", debug_mode.to_string(), source);
+        // not exporting by default
+        let (export_format, source) = layers
+            .iter()
+            .rev()
+            .find_map(|(source, layer)| layer.export_format.map(|format| (Some(format), source)))
+            .map_or((None, ConfigSource::Default), |(format, source)| {
+                (format, source.clone())
+            });
+        let value = export_format.map_or(String::new(), |format| format.to_string());
+        record("export_format", value, source);
+
+        let config = Config {
+            workflow_file_path,
+            webdriver_url,
+            headless_browser,
+            browser_args: split_browser_args(&browser_args),
+            temp_dir,
+            window_width,
+            window_height,
+            remote_wait_factor,
+            debug_mode,
+            export_format,
+            vars: BTreeMap::new(),
+        };
+        (config, values)
+    }
+}
+
+/// Split whitespace separated browser args (such as
+/// "--no-sandbox --disable-gpu") into individual args.
+pub fn split_browser_args(browser_args: &str) -> Vec<String> {
+    browser_args
         .split_ascii_whitespace()
         .map(|c| c.to_string())
-        .collect();
-
-    return Config {
-        workflow_file_path,
-        webdriver_url,
-        headless_browser,
-        browser_args,
-        temp_dir: TEMP_DIR_DEFAULT.to_string(),
-    };
+        .collect()
 }
 
 #[cfg(test)]
@@ -56,39 +282,124 @@ mod tests {
     use super::*;
 
     #[test]
-    #[should_panic(expected = "Provide configuration file (.yaml) path as the first argument!")]
-    fn parse_args_with_missing_workflow_path() {
-        let args = vec![String::from("program_name")];
-        parse_args(&args);
+    fn default_config() {
+        let config = Config::default();
+
+        assert_eq!(config.webdriver_url, WEBDRIVER_URL_DEFAULT);
+        assert_eq!(config.headless_browser, HEADLESS_BROWSER_DEFAULT);
+        assert!(config.browser_args.is_empty());
+        assert_eq!(config.temp_dir, TEMP_DIR_DEFAULT.to_string());
+        assert_eq!(config.window_width, WINDOW_WIDTH_DEFAULT);
+        assert_eq!(config.window_height, WINDOW_HEIGHT_DEFAULT);
+        assert_eq!(config.remote_wait_factor, REMOTE_WAIT_FACTOR_DEFAULT);
+        assert_eq!(config.debug_mode, DEBUG_MODE_DEFAULT);
     }
 
     #[test]
-    fn parse_args_with_workflow_path() {
-        let args = vec![
-            String::from("program_name"),
-            String::from("./sample_workflow.yaml"),
+    fn split_browser_args_on_whitespace() {
+        let args = split_browser_args(" --no-sandbox   --disable-gpu ");
+
+        assert_eq!(args, vec!["--no-sandbox", "--disable-gpu"]);
+    }
+
+    #[test]
+    fn from_layers_in_order_of_precedence() {
+        let file = ConfigLayer {
+            webdriver_url: Some("http://file:4444".to_string()),
+            temp_dir: Some("file_dir/".to_string()),
+            window_width: Some(800),
+            ..Default::default()
+        };
+        let env = ConfigLayer {
+            webdriver_url: Some("http://env:4444".to_string()),
+            temp_dir: Some("env_dir/".to_string()),
+            ..Default::default()
+        };
+        let cli = ConfigLayer {
+            temp_dir: Some("cli_dir/".to_string()),
+            ..Default::default()
+        };
+        let layers = vec![
+            (ConfigSource::File("squirrel.toml".to_string()), file),
+            (ConfigSource::Env, env),
+            (ConfigSource::Cli, cli),
         ];
-        let config = parse_args(&args);
 
-        assert_eq!(config.workflow_file_path, "./sample_workflow.yaml");
-        assert_eq!(config.headless_browser, HEADLESS_BROWSER_DEFAULT);
-        assert_eq!(config.webdriver_url, WEBDRIVER_URL_DEFAULT);
-        assert_eq!(config.temp_dir, TEMP_DIR_DEFAULT.to_string());
+        let (config, values) = Config::from_layers("workflow.yaml".to_string(), &layers);
+
+        assert_eq!(config.workflow_file_path, "workflow.yaml");
+        assert_eq!(config.webdriver_url, "http://env:4444");
+        assert_eq!(config.temp_dir, "cli_dir/");
+        assert_eq!(config.window_width, 800);
+        assert_eq!(config.window_height, WINDOW_HEIGHT_DEFAULT);
+        let source_of = |name: &str| {
+            let value = values.iter().find(|value| value.name == name).unwrap();
+            value.source.clone()
+        };
+        assert_eq!(source_of("webdriver_url"), ConfigSource::Env);
+        assert_eq!(source_of("temp_dir"), ConfigSource::Cli);
+        assert_eq!(
+            source_of("window_width"),
+            ConfigSource::File("squirrel.toml".to_string())
+        );
+        assert_eq!(source_of("window_height"), ConfigSource::Default);
     }
 
     #[test]
-    fn parse_args_with_all_arguments() {
-        let args = vec![
-            String::from("program_name"),
-            String::from("./sample_workflow.yaml"),
-            String::from("http://localhost:9515"),
-            String::from("false"),
+    fn config_layer_from_env() {
+        let vars = vec![
+            (
+                "SQUIRREL_WEBDRIVER_URL".to_string(),
+                "http://env:4444".to_string(),
+            ),
+            ("SQUIRREL_HEADLESS_BROWSER".to_string(), "false".to_string()),
+            ("SQUIRREL_EXPORT_FORMAT".to_string(), "ndjson".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
         ];
-        let config = parse_args(&args);
 
-        assert_eq!(config.workflow_file_path, "./sample_workflow.yaml");
-        assert_eq!(config.headless_browser, false);
-        assert_eq!(config.webdriver_url, WEBDRIVER_URL_DEFAULT);
-        assert_eq!(config.temp_dir, TEMP_DIR_DEFAULT.to_string());
+        let layer = ConfigLayer::from_env(vars.into_iter()).unwrap();
+
+        assert_eq!(layer.webdriver_url, Some("http://env:4444".to_string()));
+        assert_eq!(layer.headless_browser, Some(false));
+        assert_eq!(layer.export_format, Some(ExportFormat::Ndjson));
+        assert_eq!(layer.temp_dir, None);
+    }
+
+    #[test]
+    fn config_layer_from_env_with_invalid_value() {
+        let vars = vec![("SQUIRREL_WINDOW_WIDTH".to_string(), "wide".to_string())];
+
+        let result = ConfigLayer::from_env(vars.into_iter());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn config_layer_from_file() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+        let toml_path = temp_dir.path().join("squirrel.toml");
+        std::fs::write(
+            &toml_path,
+            "webdriver_url = \"http://toml:4444\"\nwindow_width = 800\n",
+        )
+        .expect("Failed to write configuration file");
+        let yaml_path = temp_dir.path().join("squirrel.yaml");
+        std::fs::write(
+            &yaml_path,
+            "temp_dir: yaml_dir/\ndebug_mode: true\nexport_format: csv\n",
+        )
+        .expect("Failed to write configuration file");
+
+        let toml_layer = ConfigLayer::from_file(toml_path.to_str().unwrap()).unwrap();
+        let yaml_layer = ConfigLayer::from_file(yaml_path.to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            toml_layer.webdriver_url,
+            Some("http://toml:4444".to_string())
+        );
+        assert_eq!(toml_layer.window_width, Some(800));
+        assert_eq!(yaml_layer.temp_dir, Some("yaml_dir/".to_string()));
+        assert_eq!(yaml_layer.debug_mode, Some(true));
+        assert_eq!(yaml_layer.export_format, Some(ExportFormat::Csv));
     }
 }
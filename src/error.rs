@@ -1,55 +1,227 @@
 use std::error;
 use std::fmt;
 
-/// A custom error type
+/// `StepPath` struct locates a step within the workflow, by the indexes of
+/// the step and of its parent steps (such as `3.1` for the second sub-step
+/// of the fourth step). An empty path refers to the workflow as a whole.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepPath(pub Vec<usize>);
+
+impl StepPath {
+    /// Return path of the sub-step at given index of the current step.
+    pub fn child(&self, index: usize) -> StepPath {
+        let mut indexes = self.0.clone();
+        indexes.push(index);
+        StepPath(indexes)
+    }
+
+    /// Return `true` if the path refers to the workflow as a whole.
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for StepPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_root() {
+            return write!(f, "workflow");
+        }
+        let indexes: Vec<String> = self.0.iter().map(|index| index.to_string()).collect();
+        write!(f, "step {}", indexes.join("."))
+    }
+}
+
+/// `SquirrelError` enum defines the errors a workflow can fail with.
+/// Each variant carries the path of the failing step.
 #[derive(Debug)]
-pub struct CustomError {
-    pub message: String,
+pub enum SquirrelError {
+    /// The workflow (or configuration) couldn't be parsed.
+    Parse { path: StepPath, message: String },
+    /// The workflow definition is invalid, such as an element step
+    /// without any located elements.
+    Validation { path: StepPath, message: String },
+    /// The WebDriver connection couldn't be established.
+    Connection { path: StepPath, message: String },
+    /// No element could be found.
+    ElementNotFound { path: StepPath, message: String },
+    /// An expectation about the page didn't hold.
+    Assertion {
+        path: StepPath,
+        message: String,
+        expected: String,
+        actual: String,
+    },
+    /// A condition wasn't met in time.
+    Timeout { path: StepPath, message: String },
+    /// A saved value couldn't be transformed, such as a text which is not
+    /// a number.
+    Transform { path: StepPath, message: String },
+    /// An I/O operation failed, such as writing a screenshot.
+    Io {
+        path: StepPath,
+        source: std::io::Error,
+    },
+    /// A WebDriver command failed.
+    Driver {
+        path: StepPath,
+        source: fantoccini::error::CmdError,
+    },
+    /// An unexpected failure within Squirrel itself, such as a panic.
+    Internal { path: StepPath, message: String },
+    /// A step (such as `BreakIfMissing`) ended the enclosing loop, which is
+    /// a failure only outside of any loop.
+    Break { path: StepPath, message: String },
+    /// A step with `on_error: skip_iteration` failed with given error, which
+    /// ends the iteration of the enclosing loop.
+    Skip {
+        path: StepPath,
+        source: Box<SquirrelError>,
+    },
+}
+
+impl SquirrelError {
+    /// Return path of the failing step.
+    pub fn path(&self) -> &StepPath {
+        match self {
+            SquirrelError::Parse { path, .. }
+            | SquirrelError::Validation { path, .. }
+            | SquirrelError::Connection { path, .. }
+            | SquirrelError::ElementNotFound { path, .. }
+            | SquirrelError::Assertion { path, .. }
+            | SquirrelError::Timeout { path, .. }
+            | SquirrelError::Transform { path, .. }
+            | SquirrelError::Io { path, .. }
+            | SquirrelError::Driver { path, .. }
+            | SquirrelError::Internal { path, .. }
+            | SquirrelError::Break { path, .. }
+            | SquirrelError::Skip { path, .. } => path,
+        }
+    }
+
+    /// Attach given step path to the error, unless it already carries the
+    /// path of a (more specific) step.
+    pub fn at(mut self, step_path: &StepPath) -> SquirrelError {
+        let path = match &mut self {
+            SquirrelError::Parse { path, .. }
+            | SquirrelError::Validation { path, .. }
+            | SquirrelError::Connection { path, .. }
+            | SquirrelError::ElementNotFound { path, .. }
+            | SquirrelError::Assertion { path, .. }
+            | SquirrelError::Timeout { path, .. }
+            | SquirrelError::Transform { path, .. }
+            | SquirrelError::Io { path, .. }
+            | SquirrelError::Driver { path, .. }
+            | SquirrelError::Internal { path, .. }
+            | SquirrelError::Break { path, .. }
+            | SquirrelError::Skip { path, .. } => path,
+        };
+        if path.is_root() {
+            *path = step_path.clone();
+        }
+        self
+    }
 }
 
-// Implement the Display trait for the custom error type
-impl fmt::Display for CustomError {
+impl fmt::Display for SquirrelError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.message)
+        let path = self.path();
+        match self {
+            SquirrelError::Parse { message, .. } => write!(f, "{path}: parse error: {message}"),
+            SquirrelError::Validation { message, .. } => {
+                write!(f, "{path}: validation error: {message}")
+            }
+            SquirrelError::Connection { message, .. } => {
+                write!(f, "{path}: connection error: {message}")
+            }
+            SquirrelError::ElementNotFound { message, .. } => {
+                write!(f, "{path}: element not found: {message}")
+            }
+            SquirrelError::Assertion {
+                message,
+                expected,
+                actual,
+                ..
+            } => write!(
+                f,
+                "{path}: assertion failed: {message} (expected: {expected:?}, actual: {actual:?})"
+            ),
+            SquirrelError::Timeout { message, .. } => write!(f, "{path}: timeout: {message}"),
+            SquirrelError::Transform { message, .. } => {
+                write!(f, "{path}: transform error: {message}")
+            }
+            SquirrelError::Io { source, .. } => write!(f, "{path}: I/O error: {source}"),
+            SquirrelError::Driver { source, .. } => write!(f, "{path}: driver error: {source}"),
+            SquirrelError::Internal { message, .. } => {
+                write!(f, "{path}: internal error: {message}")
+            }
+            SquirrelError::Break { message, .. } => write!(f, "{path}: loop break: {message}"),
+            SquirrelError::Skip { source, .. } => write!(f, "{path}: iteration skipped: {source}"),
+        }
+    }
+}
+
+impl error::Error for SquirrelError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SquirrelError::Io { source, .. } => Some(source),
+            SquirrelError::Driver { source, .. } => Some(source),
+            SquirrelError::Skip { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<fantoccini::error::CmdError> for SquirrelError {
+    fn from(source: fantoccini::error::CmdError) -> Self {
+        SquirrelError::Driver {
+            path: StepPath::default(),
+            source,
+        }
     }
 }
 
-// Implement the std::error::Error trait for the custom error type
-// to use the default implementation
-impl error::Error for CustomError {}
+impl From<std::io::Error> for SquirrelError {
+    fn from(source: std::io::Error) -> Self {
+        SquirrelError::Io {
+            path: StepPath::default(),
+            source,
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn custom_error_display_format() {
-        let error = CustomError {
-            message: String::from("This is a custom error."),
-        };
-
-        assert_eq!(format!("{}", error), "This is a custom error.");
+    fn step_path_display_format() {
+        assert_eq!(format!("{}", StepPath::default()), "workflow");
+        assert_eq!(format!("{}", StepPath(vec![3]).child(1)), "step 3.1");
     }
 
     #[test]
-    fn custom_error_debug_format() {
-        let error = CustomError {
-            message: String::from("Another custom error."),
+    fn squirrel_error_display_format() {
+        let error = SquirrelError::Assertion {
+            path: StepPath(vec![2]),
+            message: String::from("URL didn't change"),
+            expected: String::from("a different URL"),
+            actual: String::from("https://www.wikipedia.org/"),
         };
 
         assert_eq!(
-            format!("{:?}", error),
-            "CustomError { message: \"Another custom error.\" }"
+            format!("{}", error),
+            "step 2: assertion failed: URL didn't change \
+             (expected: \"a different URL\", actual: \"https://www.wikipedia.org/\")"
         );
     }
 
     #[test]
-    fn custom_error_into_string() {
-        let error = CustomError {
-            message: String::from("Yet another custom error."),
-        };
+    fn squirrel_error_at_keeps_most_specific_path() {
+        let error = SquirrelError::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+
+        let error = error.at(&StepPath(vec![4, 0])).at(&StepPath(vec![4]));
 
-        let error_string: String = error.to_string();
-        assert_eq!(error_string, "Yet another custom error.");
+        assert_eq!(error.path(), &StepPath(vec![4, 0]));
+        assert!(matches!(error, SquirrelError::Io { .. }));
     }
 }
@@ -0,0 +1,40 @@
+//! Squirrel revolutionizes browser automation by simplifying the process
+//! through YAML based workflow definition. With Squirrel, you effortlessly
+//! automate tasks without getting bogged down by technical intricacies, as
+//! the library handles all the underlying complexities for you.
+//!
+//! Besides the `squirrel-browser-automation` executable, Squirrel can be
+//! embedded into any async Rust application:
+//!
+//! ```no_run
+//! use squirrel_browser_automation::{web_driver, Config, Engine, Workflow};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let wf: Workflow = serde_yaml::from_str(
+//!     r#"
+//!     name: "example"
+//!     steps:
+//!       - !PageOpen "https://www.wikipedia.org/"
+//!       - !PageTakeScreenshot "home"
+//!     "#,
+//! )?;
+//! let config = Config::default();
+//! let capabilities = web_driver::client_capabilities(&config);
+//! let engine = Engine::connect(config, capabilities).await?;
+//! let result = engine.run(&wf).await;
+//! engine.close().await?;
+//! println!("Succeeded: {}, values: {:?}", result.is_success(), result.values);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod config;
+pub mod error;
+pub mod utils;
+pub mod web_driver;
+pub mod wf;
+
+pub use config::Config;
+pub use wf::engine::{Engine, WorkflowResult};
+pub use wf::workflow::Workflow;
+pub use wf::workflow_step::WorkflowStep;
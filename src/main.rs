@@ -1,46 +1,213 @@
-mod config;
-mod error;
-mod utils;
-mod web_driver;
-mod wf;
+mod cli;
 
-/// Squirrel revolutionizes browser automation by simplifying the process
-/// through YAML based workflow definition. With Squirrel, you effortlessly
-/// automate tasks without getting bogged down by technical intricacies, as
-/// the library handles all the underlying complexities for you.
+use clap::Parser;
+use squirrel_browser_automation::{config, error, utils, wf};
+
+/// Exit code when a workflow step (or a soft assertion) failed.
+const EXIT_STEP_FAILURE: u8 = 1;
+/// Exit code for invalid command line arguments or configurations.
+const EXIT_USAGE_ERROR: u8 = 2;
+/// Exit code when the workflow couldn't be read, parsed or validated.
+const EXIT_WORKFLOW_INVALID: u8 = 3;
+/// Exit code when the WebDriver connection couldn't be established.
+const EXIT_CONNECTION_ERROR: u8 = 4;
+/// Exit code for unexpected failures, such as failing to close the session.
+const EXIT_INTERNAL_ERROR: u8 = 5;
+
+/// Command line executable of Squirrel (refer the library documentation
+/// for embedding Squirrel in a Rust application).
 ///
 /// Here's how you can run:
 ///
 /// ```sh
-/// // with default values for all, but first argument
-/// cargo run ./src/sample_workflow.yaml
+/// // with default values for all, but the workflow path
+/// cargo run -- run ./src/sample_workflow.yaml
 ///
 /// // with default values (but provided explicitly) for webdriver_url,
 /// // headless_browser, and browser_args
-/// cargo run ./src/sample_workflow.yaml http://localhost:9515 true ""
+/// cargo run -- run ./src/sample_workflow.yaml --webdriver-url http://localhost:9515 --headless-browser true --browser-args ""
 ///
 /// // with default values (but provided explicitly) for webdriver_url
 /// // and headless_browser, but with explicit browser arg
 /// cp ./src/sample_workflow.yaml workflow.yaml
-/// cargo run workflow.yaml http://localhost:9515 true "--no-sandbox --disable-dev-shm-usage --disable-popup-blocking --disable-gpu"
+/// cargo run -- run workflow.yaml --browser-args "--no-sandbox --disable-dev-shm-usage --disable-popup-blocking --disable-gpu"
+///
+/// // with workflow variables, overriding the `vars` of the workflow
+/// cargo run -- run workflow.yaml --var term=rust --var lang=en
+///
+/// // exporting the saved values to a CSV file under `temp_dir`
+/// cargo run -- run workflow.yaml --export-format csv
+///
+/// // check the workflow, without starting the browser
+/// cargo run -- validate workflow.yaml
+///
+/// // with configurations from `squirrel.toml` (or `.yaml`), overridden by
+/// // `SQUIRREL_*` environment variables, overridden by the flags
+/// SQUIRREL_TEMP_DIR=output/ cargo run -- run workflow.yaml --config ci.toml
+/// cargo run -- config show
+///
+/// // list the supported steps, and explain one of them
+/// cargo run -- list-steps
+/// cargo run -- explain PageLocateElements
 ///
 /// // directly using build executable
-/// ./target/debug/squirrel ./src/sample_workflow.yaml http://localhost:9515 false
+/// ./target/debug/squirrel-browser-automation run ./src/sample_workflow.yaml --headless-browser false
 ///
 /// // as IDE run configuration
-/// RustRover: `run --package squirrel-browser-automation --bin squirrel-browser-automation -- workflow.yaml http://localhost:9515 false ""`
+/// RustRover: `run --package squirrel-browser-automation --bin squirrel-browser-automation -- run workflow.yaml --headless-browser false`
 /// ```
-fn main() {
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    // Invalid arguments are reported with usage, and a non-zero exit code
+    let cli = cli::Cli::parse();
+    match cli.command {
+        cli::Command::Run(run_args) => {
+            let (config_file, cli_layer) = run_args.config_args.into_layer();
+            match resolve_config(run_args.workflow_file_path, config_file, cli_layer) {
+                Ok((mut cnf, _)) => {
+                    cnf.vars.extend(run_args.vars);
+                    run(cnf).await
+                }
+                Err(exit_code) => exit_code,
+            }
+        }
+        cli::Command::Validate { workflow_file_path } => validate(workflow_file_path),
+        cli::Command::ListSteps => {
+            for (name, documentation) in wf::steps_catalog() {
+                let summary = documentation.lines().next().unwrap_or_default();
+                println!("{name:<width$}{summary}", width = 24);
+            }
+            std::process::ExitCode::SUCCESS
+        }
+        cli::Command::Explain { step } => match wf::explain_step(&step) {
+            Some((name, documentation)) => {
+                println!("{name}\n\n{documentation}");
+                std::process::ExitCode::SUCCESS
+            }
+            None => {
+                eprintln!("Unknown step: {step} (use `list-steps` to list the supported steps)");
+                std::process::ExitCode::FAILURE
+            }
+        },
+        cli::Command::Schema => {
+            println!("{:#}", wf::workflow_schema());
+            std::process::ExitCode::SUCCESS
+        }
+        cli::Command::Config {
+            command: cli::ConfigSubcommand::Show(config_args),
+        } => {
+            let (config_file, cli_layer) = config_args.into_layer();
+            match resolve_config(String::new(), config_file, cli_layer) {
+                Ok((_, values)) => {
+                    for value in values {
+                        let source = match value.source {
+                            config::ConfigSource::Env => format!(
+                                "{} ({}{})",
+                                value.source,
+                                config::ENV_PREFIX,
+                                value.name.to_ascii_uppercase()
+                            ),
+                            _ => value.source.to_string(),
+                        };
+                        println!("{} = {:?} [{source}]", value.name, value.value);
+                    }
+                    std::process::ExitCode::SUCCESS
+                }
+                Err(exit_code) => exit_code,
+            }
+        }
+    }
+}
+
+/// Resolve the configuration from the configuration file, environment
+/// variables and command line, reporting an invalid one as a usage error.
+fn resolve_config(
+    workflow_file_path: String,
+    config_file: Option<String>,
+    cli_layer: config::ConfigLayer,
+) -> Result<(config::Config, Vec<config::ConfigValue>), std::process::ExitCode> {
+    match config::load_layers(config_file.as_deref(), cli_layer) {
+        Ok(layers) => Ok(config::Config::from_layers(workflow_file_path, &layers)),
+        Err(error) => {
+            eprintln!("error: {error}");
+            Err(std::process::ExitCode::from(EXIT_USAGE_ERROR))
+        }
+    }
+}
+
+/// Run the workflow with given configuration, and print its summary.
+async fn run(cnf: config::Config) -> std::process::ExitCode {
     println!("RUN STARTED (timestamp={})", utils::timestamp());
-    let args: Vec<String> = std::env::args().collect();
-    let cnf: config::Config = config::parse_args(&args);
     println!("Squirrel executing with configuration: {:#?}", cnf);
-    match wf::invoke_workflow(&cnf) {
-        Ok(_) => println!("Successfully closed the WebDriver session!"),
-        Err(error) => panic!(
-            "Error closing the WebDriver session: {error}",
-            error = error
-        ),
-    }
+    let started_at = std::time::Instant::now();
+    let exit_code = match wf::invoke_workflow(&cnf).await {
+        Ok(result) => {
+            println!("RUN SUMMARY ({}): {}", result.name, result.summary);
+            for error in &result.soft_failures {
+                eprintln!("Soft failure: {error}");
+            }
+            match &result.error {
+                None if result.soft_failures.is_empty() => std::process::ExitCode::SUCCESS,
+                None => std::process::ExitCode::from(EXIT_STEP_FAILURE),
+                Some(error) => {
+                    eprintln!("Workflow run failed with: {error}");
+                    exit_code(error)
+                }
+            }
+        }
+        Err(error) => {
+            // No step ran, such as when the workflow is invalid
+            let summary = wf::engine::RunSummary {
+                duration: started_at.elapsed(),
+                ..Default::default()
+            };
+            println!("RUN SUMMARY ({}): {summary}", cnf.workflow_file_path);
+            eprintln!("Workflow run failed with: {error}");
+            exit_code(&error)
+        }
+    };
     println!("RUN FINISHED (timestamp={})", utils::timestamp());
+    exit_code
+}
+
+/// Return the process exit code corresponding to the error the workflow
+/// failed with, where the remaining errors raised by the steps are step failures.
+fn exit_code(error: &error::SquirrelError) -> std::process::ExitCode {
+    let code = match error {
+        error::SquirrelError::Parse { .. } | error::SquirrelError::Validation { .. } => {
+            EXIT_WORKFLOW_INVALID
+        }
+        error::SquirrelError::Connection { .. } => EXIT_CONNECTION_ERROR,
+        error::SquirrelError::Internal { .. } => EXIT_INTERNAL_ERROR,
+        _ if !error.path().is_root() => EXIT_STEP_FAILURE,
+        // failed reading the workflow file
+        error::SquirrelError::Io { .. } => EXIT_WORKFLOW_INVALID,
+        _ => EXIT_INTERNAL_ERROR,
+    };
+    std::process::ExitCode::from(code)
+}
+
+/// Validate the workflow at given path, without starting the browser.
+fn validate(workflow_file_path: String) -> std::process::ExitCode {
+    let cnf = config::Config {
+        workflow_file_path,
+        ..Default::default()
+    };
+    match wf::validate_workflow(&cnf) {
+        Ok(violations) if violations.is_empty() => {
+            println!("Workflow {} is valid.", cnf.workflow_file_path);
+            std::process::ExitCode::SUCCESS
+        }
+        Ok(violations) => {
+            eprintln!("Workflow {} is invalid:", cnf.workflow_file_path);
+            for violation in &violations {
+                eprintln!("- {violation}");
+            }
+            exit_code(&violations[0])
+        }
+        Err(error) => {
+            eprintln!("Workflow {} is invalid: {error}", cnf.workflow_file_path);
+            exit_code(&error)
+        }
+    }
 }
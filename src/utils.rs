@@ -41,8 +41,8 @@ mod tests {
         assert!(result.is_ok());
 
         // Check if the file content is as expected
-        let file_path = std::path::Path::new(&dir_path).join(&file_name);
-        let file_content = fs::read(&file_path).expect("Failed to read file");
+        let file_path = std::path::Path::new(&dir_path).join(file_name);
+        let file_content = fs::read(file_path).expect("Failed to read file");
         assert_eq!(file_content, data);
     }
 
@@ -15,7 +15,7 @@ pub fn client_capabilities(config: &config::Config) -> serde_json::Map<String, s
     let mut capabilities = serde_json::map::Map::new();
     capabilities.insert("goog:chromeOptions".to_string(), options);
 
-    return capabilities;
+    capabilities
 }
 
 #[cfg(test)]
@@ -29,9 +29,8 @@ mod tests {
         let config = config::Config {
             workflow_file_path: String::from("config.yaml"),
             headless_browser: true,
-            webdriver_url: config::WEBDRIVER_URL_DEFAULT.to_string(),
             browser_args: vec!["".to_string()],
-            temp_dir: String::from(config::TEMP_DIR_DEFAULT),
+            ..Default::default()
         };
 
         let capabilities = client_capabilities(&config);
@@ -56,8 +55,7 @@ mod tests {
                 "--disable-gpu".to_string(),
                 "--blink-settings=imagesEnabled=false".to_string(),
             ],
-            webdriver_url: config::WEBDRIVER_URL_DEFAULT.to_string(),
-            temp_dir: String::from(config::TEMP_DIR_DEFAULT),
+            ..Default::default()
         };
 
         let capabilities = client_capabilities(&config);
@@ -85,8 +83,7 @@ mod tests {
             workflow_file_path: String::from("config.yaml"),
             headless_browser: false,
             browser_args: vec!["".to_string()],
-            webdriver_url: config::WEBDRIVER_URL_DEFAULT.to_string(),
-            temp_dir: String::from(config::TEMP_DIR_DEFAULT),
+            ..Default::default()
         };
 
         let capabilities = client_capabilities(&config);
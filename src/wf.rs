@@ -1,103 +1,139 @@
-mod workflow;
-mod workflow_step;
+pub mod assertion;
+pub mod condition;
+pub mod engine;
+pub mod error_policy;
+pub mod export;
+pub mod locator;
+pub mod loops;
+pub mod step_args;
+pub mod template;
+pub mod transform;
+pub mod validator;
+pub mod value_store;
+pub mod wait;
+pub mod workflow;
+pub mod workflow_step;
 
-use crate::{config, error, utils, web_driver};
+use crate::error::{SquirrelError, StepPath};
+use crate::{config, web_driver};
 
-// The `invoke_workflow` is an async function marked to be executed by
-// tokio::main runtime, which makes it behave as if it was synchronous
-// by starting a new runtime each time it is called.
-#[tokio::main]
-pub async fn invoke_workflow(config: &config::Config) -> Result<(), fantoccini::error::CmdError> {
+/// Connect to the WebDriver, run the workflow defined in
+/// `config.workflow_file_path`, and close the WebDriver session.
+///
+/// Return an error if the workflow couldn't be run at all (such as when it
+/// couldn't be parsed), or else the result of the run.
+pub async fn invoke_workflow(
+    config: &config::Config,
+) -> Result<engine::WorkflowResult, SquirrelError> {
     // Get workflow object
-    let wf = match construct_workflow(config) {
-        Ok(value) => value,
-        Err(error) => {
-            return Err(fantoccini::error::CmdError::NotJson(format!("{error}")));
+    let wf = construct_workflow(config)?;
+    // Catch workflow definition errors before starting the browser
+    let mut violations = validator::validate(&wf);
+    if !violations.is_empty() {
+        for violation in &violations {
+            println!("Workflow definition error: {violation}");
         }
-    };
+        return Err(violations.remove(0));
+    }
 
     // Construct webdriver client
     let capabilities = web_driver::client_capabilities(config);
     println!("Using capabilities: {:#?}", capabilities);
-    let conn_webdriver = match fantoccini::ClientBuilder::native()
-        .capabilities(capabilities)
-        .connect(format!("{}", config.webdriver_url).as_str())
-        .await
-    {
-        Ok(value) => value,
+    let engine = engine::Engine::connect(config.clone(), capabilities).await?;
+    // Keep a handle to the session, to close it even if the run panics
+    let conn_webdriver = engine.client().clone();
+    // Execute all steps (on a separate task, so that a panic is caught
+    // rather than killing the process before closing the session)
+    let run = tokio::spawn(async move { engine.run(&wf).await }).await;
+    // Explicitly close webdriver client, so that the browser session is
+    // not leaked on the (possibly shared) WebDriver server
+    let closed = conn_webdriver.close().await;
+    let mut result = run.map_err(|error| {
+        let message = if error.is_panic() {
+            let panic = error.into_panic();
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            format!("Workflow run panicked: {message}")
+        } else {
+            format!("Workflow run was cancelled: {error}")
+        };
+        SquirrelError::Internal {
+            path: StepPath::default(),
+            message,
+        }
+    })?;
+    match closed {
+        Ok(_) => println!("Successfully closed the WebDriver session!"),
         Err(error) => {
-            return Err(fantoccini::error::CmdError::NotJson(format!(
-                "Failed establishing the webdriver connection: {error}"
-            )));
+            println!("Error closing the WebDriver session: {error}");
+            // don't hide the error the workflow failed with, if any
+            result.error = result.error.or(Some(error.into()));
         }
-    };
-    // The `current_elements` would hold elements found in the search
-    let mut current_elements_stack: Vec<Vec<fantoccini::elements::Element>> = Vec::new();
-    // The `current_value` would hold the values the users is interested
-    // to be provided with
-    let mut current_values: Vec<String> = Vec::new();
-    // The `depth` represents the depth of the call stack
-    let depth: usize = 0;
-
-    // Execute all steps
-    for (index, step) in wf.steps.iter().enumerate() {
+    }
+    if let Some(error) = &result.error {
         println!(
-            "Step {index}: {} [timestamp={}]",
-            step.to_string(),
-            utils::timestamp()
+            "Workflow {:?} failed at {}: {error}",
+            result.name,
+            error.path()
         );
-        // In case of an error, stop gracefully
-        match step
-            .execute(
-                config,
-                &conn_webdriver,
-                &mut current_elements_stack,
-                &mut current_values,
-                depth,
-                &wf.name,
-            )
-            .await
-        {
-            Ok(_) => {
-                // Step ran successfully!
-            }
-            Err(error) => {
-                println! {"Workflow failed with: {error}"};
-                // skipp subsequent steps
-                break;
-            }
-        };
     }
-    // Explicitly close webdriver client
-    // Although, this is not required as long as the client implements the Drop trait
-    // in which case it will be automatically dropped after going out of scope at the
-    // end of this function; provided, the function doesn't panic once the client has
-    // been created.
-    conn_webdriver.close().await
+    Ok(result)
 }
 
-fn construct_workflow(config: &config::Config) -> Result<workflow::Workflow, error::CustomError> {
+/// Read and parse the workflow at `config.workflow_file_path`.
+pub fn construct_workflow(config: &config::Config) -> Result<workflow::Workflow, SquirrelError> {
     // Read workflow steps
     let wf = match std::fs::read_to_string(&config.workflow_file_path) {
         Ok(value) => value,
         Err(error) => {
-            // converting std::io::Error to CustomError
-            return Err(error::CustomError {
-                message: format!("{error}"),
-            });
+            // mention the file being read, as std::io::Error doesn't
+            let message = format!("{}: {error}", config.workflow_file_path);
+            return Err(std::io::Error::new(error.kind(), message).into());
         }
     };
     // Parse workflow steps
     let wf: workflow::Workflow = match serde_yaml::from_str(&wf) {
         Ok(value) => value,
         Err(error) => {
-            // converting serde_yaml::Error to CustomError
-            return Err(error::CustomError {
-                message: format!("{error}"),
+            return Err(SquirrelError::Parse {
+                path: StepPath::default(),
+                message: format!("{}: {error}", config.workflow_file_path),
             });
         }
     };
-    return Ok(wf);
+    Ok(wf)
+}
+
+/// Read, parse and validate the workflow, without starting the browser.
+///
+/// Return an error if the workflow couldn't be read or parsed, or else
+/// the violations found by `validator::validate` (if any).
+pub fn validate_workflow(config: &config::Config) -> Result<Vec<SquirrelError>, SquirrelError> {
+    construct_workflow(config).map(|wf| validator::validate(&wf))
+}
+
+/// Return the name and documentation of all the supported workflow steps.
+pub fn steps_catalog() -> Vec<(String, String)> {
+    workflow_step::WorkflowStep::catalog()
+}
+
+/// Return JSON Schema of the workflow files, where each step is described
+/// in its single-entry map form (such as `PageWait: 100`), along with its
+/// documentation.
+pub fn workflow_schema() -> serde_json::Value {
+    let schema = schemars::schema_for!(workflow::Workflow);
+    serde_json::to_value(schema).unwrap_or_default()
+}
+
+/// Return the name and documentation of the workflow step with given
+/// name (case-insensitive), if such a step exists.
+pub fn explain_step(name: &str) -> Option<(String, String)> {
+    steps_catalog()
+        .into_iter()
+        .find(|(step_name, _)| step_name.eq_ignore_ascii_case(name))
 }
 
 #[cfg(test)]
@@ -146,10 +182,8 @@ mod tests {
         // Create a Config with the temporary workflow file
         let config = config::Config {
             workflow_file_path: workflow_path.to_string_lossy().to_string(),
-            headless_browser: true,
             browser_args: vec!["".to_string()],
-            webdriver_url: config::WEBDRIVER_URL_DEFAULT.to_string(),
-            temp_dir: String::from(config::TEMP_DIR_DEFAULT),
+            ..Default::default()
         };
 
         // Perform the workflow construction
@@ -162,21 +196,121 @@ mod tests {
         std::fs::remove_file(&workflow_path).expect("Failed to delete temporary workflow file");
     }
 
+    #[test]
+    fn workflow_and_step_timeouts() {
+        let content = r#"
+          name: "auto-wait"
+          timeout_ms: 5000
+          steps:
+              - !PageLocateElements ["body div", "index", 2]
+              - !PageLocateElements ["body div", "index", 2, 0, true]
+        "#;
+
+        let wf: workflow::Workflow = serde_yaml::from_str(content).expect("Failed to parse");
+
+        assert_eq!((wf.timeout_ms, wf.wait_dom_stable), (5000, false));
+        match &wf.steps[..] {
+            [workflow_step::WorkflowStep::PageLocateElements(a), workflow_step::WorkflowStep::PageLocateElements(b)] =>
+            {
+                assert_eq!((a.timeout_ms, a.wait_dom_stable), (None, None));
+                assert_eq!((b.timeout_ms, b.wait_dom_stable), (Some(0), Some(true)));
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn workflow_vars() {
+        let content = r#"
+          name: "search"
+          vars:
+            term: "rust"
+            page: 2
+          steps:
+              - !PageOpen "https://www.wikipedia.org/w/index.php?search=${term}&page=${page}"
+        "#;
+
+        let wf: workflow::Workflow = serde_yaml::from_str(content).expect("Failed to parse");
+
+        assert_eq!(wf.vars.get("term").map(String::as_str), Some("rust"));
+        assert_eq!(wf.vars.get("page").map(String::as_str), Some("2"));
+    }
+
+    #[test]
+    fn construct_sample_workflow() {
+        let config = config::Config {
+            workflow_file_path: String::from("src/sample_workflow.yaml"),
+            ..Default::default()
+        };
+
+        let result = construct_workflow(&config);
+
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+
     #[test]
     fn construct_workflow_with_invalid_file() {
         // Create a Config with a nonexistent workflow file
         let config = config::Config {
             workflow_file_path: String::from("/nonexistent/file.yaml"),
-            headless_browser: true,
             browser_args: vec!["".to_string()],
-            webdriver_url: config::WEBDRIVER_URL_DEFAULT.to_string(),
-            temp_dir: String::from(config::TEMP_DIR_DEFAULT),
+            ..Default::default()
         };
 
         // Perform the workflow construction
         let result = construct_workflow(&config);
 
         // Check if the workflow construction fails as expected
-        assert!(result.is_err());
+        assert!(matches!(result, Err(SquirrelError::Io { .. })));
+    }
+
+    #[test]
+    fn construct_workflow_with_invalid_content() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+        let workflow_path = temp_dir.path().join("test_workflow.yaml");
+        std::fs::write(
+            &workflow_path,
+            "name: \"invalid\"\nsteps:\n  - !PageFly 1\n",
+        )
+        .expect("Failed to write workflow file");
+        let config = config::Config {
+            workflow_file_path: workflow_path.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+
+        let result = construct_workflow(&config);
+
+        assert!(matches!(result, Err(SquirrelError::Parse { .. })));
+    }
+
+    #[test]
+    fn workflow_schema_describes_steps() {
+        let schema = workflow_schema();
+
+        let steps = schema["definitions"]["WorkflowStep"]["oneOf"]
+            .as_array()
+            .expect("Failed to find steps");
+        let page_wait = steps
+            .iter()
+            .find(|step| step["required"][0] == "PageWait")
+            .expect("Failed to find PageWait");
+        assert!(page_wait["description"]
+            .as_str()
+            .unwrap_or_default()
+            .starts_with("Wait for given milliseconds."));
+        // named, positional and bare forms of the arguments
+        let forms = page_wait["properties"]["PageWait"]["anyOf"]
+            .as_array()
+            .expect("Failed to find argument forms");
+        assert_eq!(forms.len(), 3);
+    }
+
+    #[test]
+    fn explain_step_ignores_case() {
+        let (name, documentation) = explain_step("pagewait").expect("Failed to find step");
+
+        assert_eq!(name, "PageWait");
+        assert!(documentation.starts_with("Wait for given milliseconds."));
+        assert!(explain_step("PageFly").is_none());
     }
 }
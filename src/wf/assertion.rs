@@ -0,0 +1,158 @@
+use crate::error::{SquirrelError, StepPath};
+use crate::wf::step_args::{self, TaggedEnum};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use serde::de::{self, Deserializer};
+use std::fmt;
+
+/// `TextMatch` enum defines how the assertion steps compare the actual text
+/// with the expected one. A plain string is expected to be equal, while the
+/// other ways are given either as a YAML tag (such as `!contains "Wiki"`),
+/// or as a single-entry map (such as `{regex: "^Wiki"}`).
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone, PartialEq)]
+#[serde(remote = "Self", rename_all = "snake_case")]
+pub enum TextMatch {
+    /// The text is exactly the given one.
+    Equals(String),
+    /// The text contains the given one.
+    Contains(String),
+    /// The text matches the given regular expression.
+    Regex(String),
+}
+
+impl Default for TextMatch {
+    fn default() -> Self {
+        TextMatch::Equals(String::new())
+    }
+}
+
+impl fmt::Display for TextMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextMatch::Equals(text) => write!(f, "{text}"),
+            TextMatch::Contains(text) => write!(f, "containing {text}"),
+            TextMatch::Regex(pattern) => write!(f, "matching {pattern}"),
+        }
+    }
+}
+
+impl serde::Serialize for TextMatch {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TextMatch::serialize(self, serializer)
+    }
+}
+
+impl<'de> TaggedEnum<'de> for TextMatch {
+    fn deserialize_variant<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TextMatch::deserialize(deserializer)
+    }
+
+    fn from_str<E: de::Error>(value: &str) -> Result<Self, E> {
+        Ok(TextMatch::Equals(value.to_string()))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for TextMatch {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        step_args::deserialize_tagged(deserializer)
+    }
+}
+
+impl TextMatch {
+    /// Return `true` if given text matches, or an error for an invalid
+    /// regular expression.
+    pub fn is_match(&self, actual: &str) -> Result<bool, regex::Error> {
+        match self {
+            TextMatch::Equals(text) => Ok(actual == text),
+            TextMatch::Contains(text) => Ok(actual.contains(text.as_str())),
+            TextMatch::Regex(pattern) => Ok(regex::Regex::new(pattern)?.is_match(actual)),
+        }
+    }
+
+    /// Return an assertion error (described by `subject`, such as "Title of
+    /// the page") unless given text matches.
+    pub fn check(&self, subject: &str, actual: &str, path: &StepPath) -> Result<(), SquirrelError> {
+        let is_match = self
+            .is_match(actual)
+            .map_err(|error| SquirrelError::Validation {
+                path: path.clone(),
+                message: format!("Invalid pattern: {error}"),
+            })?;
+        if is_match {
+            Ok(())
+        } else {
+            Err(failure(subject, self, actual, path))
+        }
+    }
+}
+
+/// Return an assertion error (described by `subject`) showing the
+/// expected and actual values.
+pub fn failure(
+    subject: &str,
+    expected: impl fmt::Display,
+    actual: impl fmt::Display,
+    path: &StepPath,
+) -> SquirrelError {
+    SquirrelError::Assertion {
+        path: path.clone(),
+        message: subject.to_string(),
+        expected: expected.to_string(),
+        actual: actual.to_string(),
+    }
+}
+
+/// Return JSON Schema of a text match, which is either a plain string
+/// (expected to be equal), or one of the ways to match.
+pub fn text_match_schema(gen: &mut SchemaGenerator) -> Schema {
+    step_args::string_or_schema::<TextMatch>(gen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_match_forms() {
+        let matches: Vec<TextMatch> =
+            serde_yaml::from_str("[\"Wikipedia\", !contains \"Wiki\", {regex: \"^W.*a$\"}]")
+                .expect("Failed to parse text matches");
+
+        assert_eq!(
+            matches,
+            vec![
+                TextMatch::Equals(String::from("Wikipedia")),
+                TextMatch::Contains(String::from("Wiki")),
+                TextMatch::Regex(String::from("^W.*a$")),
+            ]
+        );
+        for text_match in &matches {
+            assert!(text_match.is_match("Wikipedia").unwrap());
+            assert!(!text_match.is_match("Wiktionary").unwrap());
+        }
+    }
+
+    #[test]
+    fn check_shows_expected_and_actual() {
+        let text_match = TextMatch::Contains(String::from("Wiki"));
+
+        let error = text_match
+            .check("Title of the page", "Google", &StepPath(vec![1]))
+            .unwrap_err();
+
+        assert_eq!(
+            format!("{error}"),
+            "step 1: assertion failed: Title of the page \
+             (expected: \"containing Wiki\", actual: \"Google\")"
+        );
+    }
+
+    #[test]
+    fn check_rejects_invalid_pattern() {
+        let text_match = TextMatch::Regex(String::from("(unclosed"));
+
+        let result = text_match.check("URL", "https://example.com", &StepPath(vec![0]));
+
+        assert!(matches!(result, Err(SquirrelError::Validation { .. })));
+    }
+}
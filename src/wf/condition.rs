@@ -0,0 +1,241 @@
+use crate::error::{SquirrelError, StepPath};
+use crate::wf::assertion::{self, TextMatch};
+use crate::wf::locator::{self, Locator};
+use crate::wf::step_args::{self, TaggedEnum};
+use crate::wf::value_store::ValueStore;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use serde::de::{self, Deserializer};
+use std::fmt;
+
+/// `Condition` enum defines the page states (and saved values) the `If`
+/// step can branch on. A plain string is taken as a CSS selector of an
+/// element expected to exist, while the other conditions are given either
+/// as a YAML tag (such as `!url_matches "/login$"`), or as a single-entry
+/// map (such as `{url_matches: "/login$"}`).
+///
+/// The conditions are checked right away, without waiting for the elements
+/// to appear (unlike `PageLocateElements`).
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone, PartialEq)]
+#[serde(remote = "Self", rename_all = "snake_case")]
+pub enum Condition {
+    /// Any element is found by given locator, such as `!element_exists "#cookie-banner"`.
+    ElementExists(#[schemars(schema_with = "locator::locator_schema")] Locator),
+    /// The visible text of the first element found by given locator matches,
+    /// such as `!text_matches ["h1", !contains "Results"]`.
+    TextMatches(
+        #[schemars(schema_with = "locator::locator_schema")] Locator,
+        #[schemars(schema_with = "assertion::text_match_schema")] TextMatch,
+    ),
+    /// The URL of the current page matches given regular expression, such
+    /// as `!url_matches "/login$"`.
+    UrlMatches(String),
+    /// The value saved with given name (see `ValueStore::get`) matches, such
+    /// as `!value_matches ["status", "sold out"]`.
+    ValueMatches(
+        String,
+        #[schemars(schema_with = "assertion::text_match_schema")] TextMatch,
+    ),
+    /// Given JavaScript expression is truthy, such as `!js "window.loggedIn"`.
+    Js(String),
+    /// Given condition doesn't hold, such as `!not {element_exists: ".empty"}`.
+    Not(#[schemars(schema_with = "condition_schema")] Box<Condition>),
+}
+
+impl Default for Condition {
+    fn default() -> Self {
+        Condition::ElementExists(Locator::default())
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::ElementExists(locator) => write!(f, "element {locator} exists"),
+            Condition::TextMatches(locator, expected) => {
+                write!(f, "text of element {locator} is {expected}")
+            }
+            Condition::UrlMatches(pattern) => write!(f, "URL matches {pattern:?}"),
+            Condition::ValueMatches(name, expected) => write!(f, "value {name} is {expected}"),
+            Condition::Js(expression) => write!(f, "expression `{expression}` is truthy"),
+            Condition::Not(condition) => write!(f, "not ({condition})"),
+        }
+    }
+}
+
+impl serde::Serialize for Condition {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Condition::serialize(self, serializer)
+    }
+}
+
+impl<'de> TaggedEnum<'de> for Condition {
+    fn deserialize_variant<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Condition::deserialize(deserializer)
+    }
+
+    fn from_str<E: de::Error>(value: &str) -> Result<Self, E> {
+        Ok(Condition::ElementExists(Locator::Css(value.to_string())))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Condition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        step_args::deserialize_tagged(deserializer)
+    }
+}
+
+impl Condition {
+    /// Return `true` if the condition holds, or an error for an invalid
+    /// regular expression (or a failing WebDriver command).
+    #[async_recursion::async_recursion]
+    pub async fn check(
+        &self,
+        conn_webdriver: &fantoccini::Client,
+        value_store: &ValueStore,
+        path: &StepPath,
+    ) -> Result<bool, SquirrelError> {
+        let invalid_pattern = |error: regex::Error| SquirrelError::Validation {
+            path: path.clone(),
+            message: format!("Invalid pattern: {error}"),
+        };
+        match self {
+            Condition::ElementExists(locator) => {
+                Ok(!locator.find_all(conn_webdriver).await?.is_empty())
+            }
+            Condition::TextMatches(locator, expected) => {
+                match locator.find_all(conn_webdriver).await?.first() {
+                    Some(elem) => expected
+                        .is_match(&elem.text().await?)
+                        .map_err(invalid_pattern),
+                    None => Ok(false),
+                }
+            }
+            Condition::UrlMatches(pattern) => {
+                let regex = regex::Regex::new(pattern).map_err(invalid_pattern)?;
+                let url = conn_webdriver.current_url().await?;
+                Ok(regex.is_match(url.as_str()))
+            }
+            Condition::ValueMatches(name, expected) => {
+                check_value(name, expected, value_store).map_err(invalid_pattern)
+            }
+            Condition::Js(expression) => {
+                let script = format!("return !!({expression});");
+                let value = conn_webdriver.execute(&script, Vec::new()).await?;
+                Ok(value == serde_json::Value::Bool(true))
+            }
+            Condition::Not(condition) => {
+                Ok(!condition.check(conn_webdriver, value_store, path).await?)
+            }
+        }
+    }
+
+    /// Return the regular expressions of the condition (and of the negated
+    /// ones) that are invalid, along with the errors.
+    pub fn invalid_patterns(&self) -> Vec<(String, regex::Error)> {
+        let pattern = match self {
+            Condition::TextMatches(_, TextMatch::Regex(pattern))
+            | Condition::ValueMatches(_, TextMatch::Regex(pattern))
+            | Condition::UrlMatches(pattern) => pattern,
+            Condition::Not(condition) => return condition.invalid_patterns(),
+            _ => return Vec::new(),
+        };
+        match regex::Regex::new(pattern) {
+            Ok(_) => Vec::new(),
+            Err(error) => vec![(pattern.clone(), error)],
+        }
+    }
+}
+
+/// Return `true` if the value saved with given name matches, where a
+/// missing value never does.
+fn check_value(
+    name: &str,
+    expected: &TextMatch,
+    value_store: &ValueStore,
+) -> Result<bool, regex::Error> {
+    match value_store.get_text(name) {
+        Some(actual) => expected.is_match(&actual),
+        None => Ok(false),
+    }
+}
+
+/// Return JSON Schema of a condition, which is either a plain string (a
+/// CSS selector of an element expected to exist), or one of the conditions.
+pub fn condition_schema(gen: &mut SchemaGenerator) -> Schema {
+    step_args::string_or_schema::<Condition>(gen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn condition_forms() {
+        let conditions: Vec<Condition> = serde_yaml::from_str(
+            r##"
+              - "#cookie-banner"
+              - !text_matches ["h1", !contains "Results"]
+              - {url_matches: "/login$"}
+              - !not {value_matches: ["status", "sold out"]}
+              - !js "window.loggedIn"
+            "##,
+        )
+        .expect("Failed to parse conditions");
+
+        assert_eq!(
+            conditions,
+            vec![
+                Condition::ElementExists(Locator::Css(String::from("#cookie-banner"))),
+                Condition::TextMatches(
+                    Locator::Css(String::from("h1")),
+                    TextMatch::Contains(String::from("Results"))
+                ),
+                Condition::UrlMatches(String::from("/login$")),
+                Condition::Not(Box::new(Condition::ValueMatches(
+                    String::from("status"),
+                    TextMatch::Equals(String::from("sold out"))
+                ))),
+                Condition::Js(String::from("window.loggedIn")),
+            ]
+        );
+        assert_eq!(
+            format!("{}", conditions[3]),
+            "not (value status is sold out)"
+        );
+    }
+
+    #[test]
+    fn check_saved_values() {
+        let mut store = ValueStore::new();
+        store.save("status", serde_json::json!("Sold out!"));
+
+        assert!(check_value(
+            "status",
+            &TextMatch::Regex(String::from("(?i)sold out")),
+            &store
+        )
+        .unwrap());
+        assert!(!check_value(
+            "status",
+            &TextMatch::Equals(String::from("sold out")),
+            &store
+        )
+        .unwrap());
+        assert!(!check_value("missing", &TextMatch::Contains(String::new()), &store).unwrap());
+    }
+
+    #[test]
+    fn invalid_patterns_of_nested_conditions() {
+        let condition: Condition = serde_yaml::from_str(r#"!not {url_matches: "(unclosed"}"#)
+            .expect("Failed to parse condition");
+
+        let invalid = condition.invalid_patterns();
+
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].0, "(unclosed");
+        assert!(Condition::Js(String::from("("))
+            .invalid_patterns()
+            .is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+use crate::error::{SquirrelError, StepPath};
+use crate::wf::value_store::{Record, ValueStore};
+use crate::wf::{export, workflow};
+use crate::{config, utils};
+use std::fmt;
+
+/// `Engine` struct executes parsed workflows using a connected WebDriver client.
+///
+/// It is async-native, and so it can be embedded into any application
+/// already running within a tokio runtime.
+pub struct Engine {
+    config: config::Config,
+    conn_webdriver: fantoccini::Client,
+}
+
+/// `WorkflowResult` struct describes the outcome of running a workflow.
+#[derive(Debug)]
+pub struct WorkflowResult {
+    /// Name of the workflow.
+    pub name: String,
+    /// Values saved by the workflow steps (such as `ElementSaveHtmlValue`)
+    /// by name, along with the lists of records saved by the loops with
+    /// `record` (see `ValueStore`).
+    pub values: Record,
+    /// The error the workflow failed with (along with the failing step), if any.
+    pub error: Option<SquirrelError>,
+    /// The failed assertions collected in soft-assert mode (see
+    /// `Workflow::soft_assertions`), along with the errors of the steps
+    /// whose `on_error` let the run go on (see `ErrorPolicy`), in order.
+    pub soft_failures: Vec<SquirrelError>,
+    /// Counts of the (top-level) steps, and the duration of the run.
+    pub summary: RunSummary,
+}
+
+/// `RunSummary` struct counts the top-level steps of a workflow run by
+/// their outcome, where the steps following a failing one are skipped, and
+/// a step that let the run go on despite a failure (such as a failed soft
+/// assertion) counts as failed.
+#[derive(Debug, Default)]
+pub struct RunSummary {
+    pub executed: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub duration: std::time::Duration,
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "steps executed: {}, passed: {}, failed: {}, skipped: {}, duration: {:.3}s",
+            self.executed,
+            self.passed,
+            self.failed,
+            self.skipped,
+            self.duration.as_secs_f64()
+        )
+    }
+}
+
+impl WorkflowResult {
+    /// Return `true` if all the steps of the workflow ran successfully,
+    /// without any failed (soft) assertion.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none() && self.soft_failures.is_empty()
+    }
+}
+
+impl Engine {
+    /// Construct an engine using an already-connected WebDriver client.
+    pub fn new(config: config::Config, conn_webdriver: fantoccini::Client) -> Engine {
+        Engine {
+            config,
+            conn_webdriver,
+        }
+    }
+
+    /// Construct an engine by connecting to `config.webdriver_url` with
+    /// given capabilities (such as the ones from `web_driver::client_capabilities`).
+    pub async fn connect(
+        config: config::Config,
+        capabilities: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Engine, SquirrelError> {
+        let conn_webdriver = fantoccini::ClientBuilder::native()
+            .capabilities(capabilities)
+            .connect(config.webdriver_url.as_str())
+            .await
+            .map_err(|error| SquirrelError::Connection {
+                path: StepPath::default(),
+                message: format!(
+                    "Failed establishing the webdriver connection to {}: {error}",
+                    config.webdriver_url
+                ),
+            })?;
+        Ok(Engine::new(config, conn_webdriver))
+    }
+
+    /// Return the WebDriver client used by the engine.
+    pub fn client(&self) -> &fantoccini::Client {
+        &self.conn_webdriver
+    }
+
+    /// Run all steps of given workflow, stopping at the first failing one.
+    pub async fn run(&self, wf: &workflow::Workflow) -> WorkflowResult {
+        // The `current_elements` would hold elements found in the search
+        let mut current_elements_stack: Vec<Vec<fantoccini::elements::Element>> = Vec::new();
+        // The `value_store` would hold the values the users is interested
+        // to be provided with
+        let mut value_store = ValueStore::new();
+        // The `soft_failures` would hold the failed assertions, in soft-assert mode
+        let mut soft_failures: Vec<SquirrelError> = Vec::new();
+        // The `depth` represents the depth of the call stack
+        let depth: usize = 0;
+        let mut result_error = None;
+        let mut summary = RunSummary::default();
+        let started_at = std::time::Instant::now();
+
+        // Execute all steps
+        for (index, step) in wf.steps.iter().enumerate() {
+            println!(
+                "Step {index}: {} [timestamp={}]",
+                step.to_string(),
+                utils::timestamp()
+            );
+            summary.executed += 1;
+            let soft_failures_before = soft_failures.len();
+            // In case of an error, stop gracefully
+            match step
+                .execute(
+                    &self.config,
+                    &self.conn_webdriver,
+                    &mut current_elements_stack,
+                    &mut value_store,
+                    &mut soft_failures,
+                    &[],
+                    depth,
+                    wf,
+                    &StepPath(vec![index]),
+                )
+                .await
+            {
+                Ok(_) if soft_failures.len() > soft_failures_before => {
+                    // The run goes on, but the step (or one of its sub-steps) failed
+                    summary.failed += 1;
+                }
+                Ok(_) => {
+                    // Step ran successfully!
+                    summary.passed += 1;
+                }
+                Err(error) => {
+                    // Outside of any loop, there is no iteration to skip
+                    let error = match error {
+                        SquirrelError::Skip { source, .. } => *source,
+                        error => error,
+                    };
+                    println! {"Workflow failed with: {error}"};
+                    summary.failed += 1;
+                    summary.skipped = wf.steps.len() - index - 1;
+                    result_error = Some(error);
+                    // skipp subsequent steps
+                    break;
+                }
+            };
+        }
+        summary.duration = started_at.elapsed();
+        // Export the values, even if a step failed, to keep what got scraped
+        if let Some(format) = self.config.export_format {
+            let values = value_store.root();
+            let path = StepPath::default();
+            match export::write(
+                &self.config.temp_dir,
+                &wf.name,
+                "",
+                values,
+                format,
+                None,
+                &path,
+            ) {
+                Ok(file_name) => println!("Exported values to: {file_name}"),
+                Err(error) => {
+                    println!("Failed to export values: {error}");
+                    // not to be taken for an invalid workflow (the root I/O error)
+                    result_error.get_or_insert(SquirrelError::Internal {
+                        path,
+                        message: format!("Failed to export values: {error}"),
+                    });
+                }
+            }
+        }
+        if !soft_failures.is_empty() {
+            println!("Soft failures: {}", soft_failures.len());
+            for error in &soft_failures {
+                println!("{:>width$}{error}", "", width = config::TAB_SIZE);
+            }
+        }
+        WorkflowResult {
+            name: wf.name.clone(),
+            values: value_store.into_root(),
+            error: result_error,
+            soft_failures,
+            summary,
+        }
+    }
+
+    /// Explicitly close the WebDriver session.
+    pub async fn close(self) -> Result<(), SquirrelError> {
+        Ok(self.conn_webdriver.close().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_summary_display_format() {
+        let summary = RunSummary {
+            executed: 3,
+            passed: 2,
+            failed: 1,
+            skipped: 4,
+            duration: std::time::Duration::from_millis(1500),
+        };
+
+        assert_eq!(
+            format!("{summary}"),
+            "steps executed: 3, passed: 2, failed: 1, skipped: 4, duration: 1.500s"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::wf::step_args::{self, TaggedEnum};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject, SubschemaValidation};
+use serde::de::Deserializer;
+use std::fmt;
+use std::time::Duration;
+
+/// `ErrorPolicy` enum defines what happens when a step fails, given as
+/// `on_error` of the step (such as `!ElementSaveText {name: "price",
+/// on_error: skip_iteration}`), or of the workflow. The ones
+/// without arguments are given by name (such as `continue`), while `retry`
+/// is given either as a YAML tag (such as `!retry {times: 3, backoff_ms: 500}`),
+/// or as a single-entry map (such as `{retry: {times: 3}}`).
+///
+/// A loop break (such as by `BreakIfMissing`) is never handled by the policy.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(remote = "Self", rename_all = "snake_case")]
+pub enum ErrorPolicy {
+    /// Fail the workflow (or the enclosing loop, see `break_on`).
+    #[default]
+    Fail,
+    /// Report the error at the end of the run, and go on with the next step.
+    Continue,
+    /// Report the error at the end of the run, and go on with the next
+    /// iteration of the enclosing loop (such as the next product of
+    /// `ElementsLoopThrough`), or else fail the workflow outside of any loop.
+    SkipIteration,
+    /// Run the step again, up to given number of times, waiting `backoff_ms`
+    /// before the first retry and twice as long before each next one, and
+    /// then fail.
+    Retry {
+        times: usize,
+        #[serde(default)]
+        backoff_ms: u64,
+    },
+}
+
+impl fmt::Display for ErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorPolicy::Fail => write!(f, "fail"),
+            ErrorPolicy::Continue => write!(f, "continue"),
+            ErrorPolicy::SkipIteration => write!(f, "skip_iteration"),
+            ErrorPolicy::Retry { times, backoff_ms } => {
+                write!(f, "retry({times}, backoff {backoff_ms}ms)")
+            }
+        }
+    }
+}
+
+impl serde::Serialize for ErrorPolicy {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ErrorPolicy::serialize(self, serializer)
+    }
+}
+
+impl<'de> TaggedEnum<'de> for ErrorPolicy {
+    fn deserialize_variant<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ErrorPolicy::deserialize(deserializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ErrorPolicy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        step_args::deserialize_tagged(deserializer)
+    }
+}
+
+impl ErrorPolicy {
+    /// Return the time to wait before running the step again, given the
+    /// number of retries so far, or `None` if it is not to be retried.
+    pub fn retry_delay(&self, retries: usize) -> Option<Duration> {
+        match self {
+            ErrorPolicy::Retry { times, backoff_ms } if retries < *times => {
+                let factor = 1u64.checked_shl(retries as u32).unwrap_or(u64::MAX);
+                Some(Duration::from_millis(backoff_ms.saturating_mul(factor)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Return JSON Schema of an error policy, which is either the name of a
+/// policy (such as "continue"), or `retry` along with its arguments.
+pub fn error_policy_schema(gen: &mut SchemaGenerator) -> Schema {
+    step_args::string_or_schema::<ErrorPolicy>(gen)
+}
+
+/// Return JSON Schema of the `on_error` of a step, which is either an error
+/// policy, or `null` to follow the one of the workflow.
+pub fn optional_error_policy_schema(gen: &mut SchemaGenerator) -> Schema {
+    SchemaObject {
+        subschemas: Some(Box::new(SubschemaValidation {
+            any_of: Some(vec![
+                error_policy_schema(gen),
+                SchemaObject {
+                    instance_type: Some(InstanceType::Null.into()),
+                    ..Default::default()
+                }
+                .into(),
+            ]),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_policy_forms() {
+        let policies: Vec<ErrorPolicy> = serde_yaml::from_str(
+            r#"
+              - fail
+              - continue
+              - skip_iteration
+              - !retry {times: 3, backoff_ms: 500}
+              - {retry: {times: 2}}
+            "#,
+        )
+        .expect("Failed to parse error policies");
+
+        assert_eq!(
+            policies,
+            vec![
+                ErrorPolicy::Fail,
+                ErrorPolicy::Continue,
+                ErrorPolicy::SkipIteration,
+                ErrorPolicy::Retry {
+                    times: 3,
+                    backoff_ms: 500
+                },
+                ErrorPolicy::Retry {
+                    times: 2,
+                    backoff_ms: 0
+                },
+            ]
+        );
+        assert_eq!(format!("{}", policies[3]), "retry(3, backoff 500ms)");
+    }
+
+    #[test]
+    fn retry_delay_backs_off() {
+        let policy = ErrorPolicy::Retry {
+            times: 3,
+            backoff_ms: 100,
+        };
+
+        let delays: Vec<Option<Duration>> =
+            (0..4).map(|retries| policy.retry_delay(retries)).collect();
+
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_millis(100)),
+                Some(Duration::from_millis(200)),
+                Some(Duration::from_millis(400)),
+                None
+            ]
+        );
+        assert_eq!(ErrorPolicy::Continue.retry_delay(0), None);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::error::{SquirrelError, StepPath};
+use crate::utils;
+use crate::wf::value_store::{self, Record};
+use serde_json::Value;
+
+/// `ExportFormat` enum defines the file formats the saved values can be
+/// exported to (by `ExportValues`, or at the end of the run).
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ExportFormat {
+    /// All the values as a single JSON object.
+    #[default]
+    Json,
+    /// One JSON object per line, for each of the rows.
+    Ndjson,
+    /// Comma separated values, with a header line, for each of the rows.
+    Csv,
+}
+
+/// Return the rows of given values to export: the records of the list with
+/// given name, or else the records of all the lists of records (such as
+/// saved by the loops with `record`), or else the values themselves as a
+/// single row.
+pub fn rows(values: &Record, records: Option<&str>) -> Vec<Record> {
+    let as_row = |value: &Value, name: &str| match value {
+        Value::Object(record) => record.clone(),
+        value => Record::from_iter([(name.to_string(), value.clone())]),
+    };
+    if let Some(name) = records {
+        return match values.get(name) {
+            Some(Value::Array(list)) => list.iter().map(|value| as_row(value, name)).collect(),
+            Some(value) => vec![as_row(value, name)],
+            None => Vec::new(),
+        };
+    }
+    let rows: Vec<Record> = values
+        .values()
+        .filter_map(|value| match value {
+            Value::Array(list) if list.iter().all(Value::is_object) => Some(list),
+            _ => None,
+        })
+        .flatten()
+        .map(|value| as_row(value, ""))
+        .collect();
+    if rows.is_empty() && !values.is_empty() {
+        return vec![values.clone()];
+    }
+    rows
+}
+
+/// Return given values rendered in given format (see `rows` for the rows
+/// of NDJSON and CSV).
+pub fn render(
+    values: &Record,
+    format: ExportFormat,
+    records: Option<&str>,
+) -> Result<String, serde_json::Error> {
+    match format {
+        ExportFormat::Json => {
+            let values = match records {
+                Some(name) => Value::Array(
+                    rows(values, Some(name))
+                        .into_iter()
+                        .map(Value::Object)
+                        .collect(),
+                ),
+                None => Value::Object(values.clone()),
+            };
+            Ok(format!("{}\n", serde_json::to_string_pretty(&values)?))
+        }
+        ExportFormat::Ndjson => {
+            let mut content = String::new();
+            for row in rows(values, records) {
+                content.push_str(&serde_json::to_string(&row)?);
+                content.push('\n');
+            }
+            Ok(content)
+        }
+        ExportFormat::Csv => Ok(render_csv(&rows(values, records))),
+    }
+}
+
+/// Return given rows as CSV, with the columns in the order they first
+/// appear in, where the nested values (lists and records) are written as JSON.
+fn render_csv(rows: &[Record]) -> String {
+    let mut columns: Vec<&String> = Vec::new();
+    for key in rows.iter().flat_map(|row| row.keys()) {
+        if !columns.contains(&key) {
+            columns.push(key);
+        }
+    }
+    let mut content = String::new();
+    let mut push_line = |fields: Vec<String>| {
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        content.push_str(&fields.join(","));
+        content.push('\n');
+    };
+    push_line(columns.iter().map(|column| column.to_string()).collect());
+    for row in rows {
+        push_line(
+            columns
+                .iter()
+                .map(|column| match row.get(column.as_str()) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(value) => value_store::to_text(value),
+                })
+                .collect(),
+        );
+    }
+    content
+}
+
+/// Quote given CSV field, if it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Write given values in given format to a file under given dir, named
+/// after the workflow (and given prefix, if any) like the screenshots, and
+/// return the name of the file.
+pub fn write(
+    dir: &String,
+    wf_name: &str,
+    file_prefix: &str,
+    values: &Record,
+    format: ExportFormat,
+    records: Option<&str>,
+    path: &StepPath,
+) -> Result<String, SquirrelError> {
+    let content = render(values, format, records).map_err(|error| SquirrelError::Internal {
+        path: path.clone(),
+        message: format!("Failed to render the values as {format}: {error}"),
+    })?;
+    let file_name = match file_prefix {
+        "" => format!("{wf_name}_{}.{format}", utils::timestamp()),
+        _ => format!("{wf_name}_{file_prefix}_{}.{format}", utils::timestamp()),
+    };
+    utils::write_file(dir, &file_name, &content.into_bytes())?;
+    Ok(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn values() -> Record {
+        match json!({
+            "page": 1,
+            "products": [
+                {"name": "Pen", "price": 2.5},
+                {"name": "Ink, blue", "tags": ["a", "b"]},
+            ]
+        }) {
+            Value::Object(values) => values,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn rows_of_lists_of_records() {
+        let values = values();
+
+        assert_eq!(rows(&values, None).len(), 2);
+        assert_eq!(rows(&values, Some("products")), rows(&values, None));
+        assert_eq!(
+            rows(&values, Some("page")),
+            vec![Record::from_iter([(String::from("page"), json!(1))])]
+        );
+        let flat = Record::from_iter([(String::from("title"), json!("Wikipedia"))]);
+        assert_eq!(rows(&flat, None), vec![flat.clone()]);
+        assert!(rows(&Record::new(), None).is_empty());
+    }
+
+    #[test]
+    fn render_formats() {
+        let values = values();
+
+        assert_eq!(
+            render(&values, ExportFormat::Ndjson, None).unwrap(),
+            "{\"name\":\"Pen\",\"price\":2.5}\n{\"name\":\"Ink, blue\",\"tags\":[\"a\",\"b\"]}\n"
+        );
+        assert_eq!(
+            render(&values, ExportFormat::Csv, None).unwrap(),
+            "name,price,tags\nPen,2.5,\n\"Ink, blue\",,\"[\"\"a\"\",\"\"b\"\"]\"\n"
+        );
+        let json: Value =
+            serde_json::from_str(&render(&values, ExportFormat::Json, None).unwrap()).unwrap();
+        assert_eq!(json, Value::Object(values));
+    }
+
+    #[test]
+    fn write_file_named_after_workflow() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+        let dir = temp_dir.path().to_str().unwrap().to_string();
+
+        let file_name = write(
+            &dir,
+            "shop",
+            "products",
+            &values(),
+            ExportFormat::Csv,
+            None,
+            &StepPath::default(),
+        )
+        .expect("Failed to export values");
+
+        assert!(file_name.starts_with("shop_products_"));
+        assert!(file_name.ends_with(".csv"));
+        let content = std::fs::read_to_string(temp_dir.path().join(&file_name)).unwrap();
+        assert!(content.starts_with("name,price,tags\n"));
+    }
+}
@@ -0,0 +1,206 @@
+use crate::wf::step_args::{self, TaggedEnum};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use serde::de::{self, Deserializer};
+use std::fmt;
+
+/// `Locator` enum defines the strategies to find elements with. A plain
+/// string is taken as a CSS selector, while the other strategies are given
+/// either as a YAML tag (such as `!xpath "//div"`), or as a single-entry
+/// map (such as `{xpath: "//div"}`).
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone, PartialEq)]
+#[serde(remote = "Self", rename_all = "snake_case")]
+pub enum Locator {
+    /// CSS selector, such as `body div.card`.
+    Css(String),
+    /// XPath expression, such as `//div[@class='card']` (or `.//span`, relative
+    /// to the current element within `ElementLocateChildren`).
+    Xpath(String),
+    /// Exact visible text of the `a` elements.
+    LinkText(String),
+    /// Part of the visible text of the `a` elements.
+    PartialLinkText(String),
+    /// Value of the `id` attribute.
+    Id(String),
+    /// Part of the text (directly) within the elements, for any kind of element.
+    TextContains(String),
+}
+
+impl Default for Locator {
+    fn default() -> Self {
+        Locator::Css(String::new())
+    }
+}
+
+impl fmt::Display for Locator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Locator::Css(css) => write!(f, "css={css}"),
+            Locator::Xpath(xpath) => write!(f, "xpath={xpath}"),
+            Locator::LinkText(text) => write!(f, "link_text={text}"),
+            Locator::PartialLinkText(text) => write!(f, "partial_link_text={text}"),
+            Locator::Id(id) => write!(f, "id={id}"),
+            Locator::TextContains(text) => write!(f, "text_contains={text}"),
+        }
+    }
+}
+
+impl serde::Serialize for Locator {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Locator::serialize(self, serializer)
+    }
+}
+
+impl<'de> TaggedEnum<'de> for Locator {
+    fn deserialize_variant<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Locator::deserialize(deserializer)
+    }
+
+    fn from_str<E: de::Error>(value: &str) -> Result<Self, E> {
+        Ok(Locator::Css(value.to_string()))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Locator {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        step_args::deserialize_tagged(deserializer)
+    }
+}
+
+impl Locator {
+    /// Find all the elements on the current page.
+    pub async fn find_all(
+        &self,
+        conn_webdriver: &fantoccini::Client,
+    ) -> Result<Vec<fantoccini::elements::Element>, fantoccini::error::CmdError> {
+        let mut xpath = String::new();
+        conn_webdriver
+            .find_all(self.to_fantoccini(&mut xpath))
+            .await
+    }
+
+    /// Find all the elements within given element.
+    pub async fn find_all_in(
+        &self,
+        elem: &fantoccini::elements::Element,
+    ) -> Result<Vec<fantoccini::elements::Element>, fantoccini::error::CmdError> {
+        let mut xpath = String::new();
+        elem.find_all(self.to_fantoccini(&mut xpath)).await
+    }
+
+    /// Return the equivalent fantoccini locator. The strategies not
+    /// supported by WebDriver are translated to (relative) XPath, which is
+    /// kept in given buffer.
+    fn to_fantoccini<'a>(&'a self, xpath: &'a mut String) -> fantoccini::Locator<'a> {
+        match self {
+            Locator::Css(css) => fantoccini::Locator::Css(css),
+            Locator::Xpath(xpath) => fantoccini::Locator::XPath(xpath),
+            Locator::LinkText(text) => fantoccini::Locator::LinkText(text),
+            // fantoccini puts the id into absolute XPath as it is, which breaks
+            // on quotes, and looks up the whole page from within an element
+            Locator::Id(id) => {
+                *xpath = format!(".//*[@id={}]", xpath_literal(id));
+                fantoccini::Locator::XPath(xpath)
+            }
+            Locator::PartialLinkText(text) => {
+                *xpath = format!(
+                    ".//a[contains(normalize-space(.), {})]",
+                    xpath_literal(text)
+                );
+                fantoccini::Locator::XPath(xpath)
+            }
+            Locator::TextContains(text) => {
+                *xpath = format!(".//*[text()[contains(., {})]]", xpath_literal(text));
+                fantoccini::Locator::XPath(xpath)
+            }
+        }
+    }
+}
+
+/// Return given text as XPath string literal. XPath has no escaping, and
+/// so the text with both kinds of quotes is split using `concat()`.
+fn xpath_literal(text: &str) -> String {
+    if !text.contains('"') {
+        format!("\"{text}\"")
+    } else if !text.contains('\'') {
+        format!("'{text}'")
+    } else {
+        let parts: Vec<String> = text.split('"').map(|part| format!("\"{part}\"")).collect();
+        format!("concat({})", parts.join(", '\"', "))
+    }
+}
+
+/// Return JSON Schema of a locator, which is either a plain CSS selector,
+/// or one of the locator strategies.
+pub fn locator_schema(gen: &mut SchemaGenerator) -> Schema {
+    step_args::string_or_schema::<Locator>(gen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locator_forms() {
+        let locators: Vec<Locator> =
+            serde_yaml::from_str("[\"body div\", !xpath \"//div\", {partial_link_text: \"Next\"}]")
+                .expect("Failed to parse locators");
+
+        assert_eq!(
+            locators,
+            vec![
+                Locator::Css(String::from("body div")),
+                Locator::Xpath(String::from("//div")),
+                Locator::PartialLinkText(String::from("Next")),
+            ]
+        );
+        let result: Result<Locator, _> = serde_yaml::from_str("{name: \"q\"}");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn text_strategies_translate_to_xpath() {
+        let mut xpath = String::new();
+
+        let locator = Locator::TextContains(String::from("Add to cart"));
+        match locator.to_fantoccini(&mut xpath) {
+            fantoccini::Locator::XPath(xpath) => {
+                assert_eq!(xpath, ".//*[text()[contains(., \"Add to cart\")]]")
+            }
+            other => panic!("Unexpected locator: {:?}", other),
+        }
+        let locator = Locator::Id(String::from("say \"hi\""));
+        match locator.to_fantoccini(&mut xpath) {
+            fantoccini::Locator::XPath(xpath) => assert_eq!(xpath, ".//*[@id='say \"hi\"']"),
+            other => panic!("Unexpected locator: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn translated_locators_are_relative() {
+        let locators = [
+            Locator::Id(String::from("price")),
+            Locator::PartialLinkText(String::from("Next")),
+            Locator::TextContains(String::from("Add to cart")),
+        ];
+
+        for locator in locators {
+            let mut xpath = String::new();
+            match locator.to_fantoccini(&mut xpath) {
+                // within `ElementLocateChildren`, search the current element only
+                fantoccini::Locator::XPath(xpath) => assert!(xpath.starts_with(".//"), "{xpath}"),
+                other => panic!("Unexpected locator: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn xpath_literal_quoting() {
+        assert_eq!(xpath_literal("Next"), "\"Next\"");
+        assert_eq!(xpath_literal("say \"hi\""), "'say \"hi\"'");
+        assert_eq!(
+            xpath_literal("it's \"hi\""),
+            "concat(\"it's \", '\"', \"hi\", '\"', \"\")"
+        );
+    }
+}
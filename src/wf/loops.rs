@@ -0,0 +1,171 @@
+use crate::error::SquirrelError;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// `LoopEnd` enum defines the reasons a loop (such as `PageLoop`) stopped,
+/// to be reported along with the number of iterations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoopEnd {
+    /// All the elements were looped through.
+    Completed,
+    /// The condition of `While` doesn't hold anymore.
+    ConditionFalse,
+    /// Given number of iterations ran (such as `times` of `Repeat`).
+    MaxIterations(usize),
+    /// Given time passed since the loop started.
+    MaxDuration(u64),
+    /// A sub-step failed with an error the loop breaks on (see `BreakOn`),
+    /// such as no longer finding the "Next" element in pagination.
+    Error(String),
+    /// A sub-step (such as `BreakIfMissing`) told the loop to stop.
+    Break(String),
+}
+
+impl fmt::Display for LoopEnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopEnd::Completed => write!(f, "completed"),
+            LoopEnd::ConditionFalse => write!(f, "condition no longer holds"),
+            LoopEnd::MaxIterations(max) => write!(f, "limit of {max} iterations reached"),
+            LoopEnd::MaxDuration(max_ms) => write!(f, "limit of {max_ms}ms reached"),
+            LoopEnd::Error(error) => write!(f, "ended by error: {error}"),
+            LoopEnd::Break(message) => write!(f, "break: {message}"),
+        }
+    }
+}
+
+/// `LoopGuard` struct bounds the iterations of a loop by their number, and
+/// by the time since the loop started.
+#[derive(Debug)]
+pub struct LoopGuard {
+    max_iterations: Option<usize>,
+    max_duration_ms: Option<u64>,
+    started_at: Instant,
+    iterations: usize,
+}
+
+impl LoopGuard {
+    /// Construct a guard (starting the clock), where `None` means unbounded.
+    pub fn new(max_iterations: Option<usize>, max_duration_ms: Option<u64>) -> LoopGuard {
+        LoopGuard {
+            max_iterations,
+            max_duration_ms,
+            started_at: Instant::now(),
+            iterations: 0,
+        }
+    }
+
+    /// Return the limit reached before starting another iteration, if any,
+    /// or else count the iteration.
+    pub fn next_iteration(&mut self) -> Option<LoopEnd> {
+        match (self.max_iterations, self.max_duration_ms) {
+            (Some(max), _) if self.iterations >= max => Some(LoopEnd::MaxIterations(max)),
+            (_, Some(max_ms)) if self.started_at.elapsed() >= Duration::from_millis(max_ms) => {
+                Some(LoopEnd::MaxDuration(max_ms))
+            }
+            _ => {
+                self.iterations += 1;
+                None
+            }
+        }
+    }
+
+    /// Return the number of iterations started so far.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+}
+
+/// `BreakOn` enum defines the errors of the sub-steps a loop can be told
+/// to end on (by its `break_on`), rather than failing the workflow.
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BreakOn {
+    /// No element could be found, such as the "Next" element on the last page.
+    ElementNotFound,
+    /// A condition wasn't met in time, such as by `WaitForElement`.
+    Timeout,
+    /// An assertion failed, such as clicking a disabled element with `check_enabled`.
+    Assertion,
+}
+
+impl BreakOn {
+    /// Return `true` if given error is of this kind.
+    pub fn matches(&self, error: &SquirrelError) -> bool {
+        matches!(
+            (self, error),
+            (
+                BreakOn::ElementNotFound,
+                SquirrelError::ElementNotFound { .. }
+            ) | (BreakOn::Timeout, SquirrelError::Timeout { .. })
+                | (BreakOn::Assertion, SquirrelError::Assertion { .. })
+        )
+    }
+}
+
+/// Return the errors `PageLoop` breaks on by default: an element not found,
+/// such as the "Next" element on the last page.
+pub fn default_page_loop_break_on() -> Vec<BreakOn> {
+    vec![BreakOn::ElementNotFound]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::StepPath;
+
+    #[test]
+    fn guard_bounds_iterations() {
+        let mut guard = LoopGuard::new(Some(2), None);
+
+        assert_eq!(guard.next_iteration(), None);
+        assert_eq!(guard.next_iteration(), None);
+        assert_eq!(guard.next_iteration(), Some(LoopEnd::MaxIterations(2)));
+        assert_eq!(guard.iterations(), 2);
+
+        let mut unbounded = LoopGuard::new(None, None);
+        assert!((0..1000).all(|_| unbounded.next_iteration().is_none()));
+    }
+
+    #[test]
+    fn guard_bounds_duration() {
+        let mut guard = LoopGuard::new(Some(10), Some(0));
+
+        assert_eq!(guard.next_iteration(), Some(LoopEnd::MaxDuration(0)));
+        assert_eq!(guard.iterations(), 0);
+        assert_eq!(
+            format!("{}", LoopEnd::MaxDuration(60000)),
+            "limit of 60000ms reached"
+        );
+    }
+
+    #[test]
+    fn break_on_matches_error_kind() {
+        let path = StepPath(vec![3, 1]);
+        let not_found = SquirrelError::ElementNotFound {
+            path: path.clone(),
+            message: String::from("No element matching css=a.next"),
+        };
+        let io = SquirrelError::Io {
+            path,
+            source: std::io::Error::new(std::io::ErrorKind::Other, "disk full"),
+        };
+        let break_on: Vec<BreakOn> =
+            serde_yaml::from_str("[element_not_found, timeout]").expect("Failed to parse");
+
+        assert!(break_on.iter().any(|kind| kind.matches(&not_found)));
+        assert!(!break_on.iter().any(|kind| kind.matches(&io)));
+        assert!(!BreakOn::Assertion.matches(&not_found));
+        assert_eq!(default_page_loop_break_on(), vec![BreakOn::ElementNotFound]);
+    }
+}
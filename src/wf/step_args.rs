@@ -0,0 +1,1368 @@
+use crate::wf::assertion::{self, TextMatch};
+use crate::wf::condition::{self, Condition};
+use crate::wf::error_policy::{self, ErrorPolicy};
+use crate::wf::export::ExportFormat;
+use crate::wf::locator::{self, Locator};
+use crate::wf::loops::{self, BreakOn};
+use crate::wf::transform::Transform;
+use crate::wf::workflow_step::WorkflowStep;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{
+    ArrayValidation, InstanceType, Schema, SchemaObject, SingleOrVec, SubschemaValidation,
+};
+use schemars::JsonSchema;
+use serde::de::{self, Deserialize, Deserializer, IntoDeserializer, Visitor};
+use std::fmt;
+use std::marker::PhantomData;
+
+// Arguments of the `WorkflowStep` variants.
+//
+// Each of them can be given either as positional values (such as
+// `!ElementClick [true, false]`), or as named values (such as
+// `!ElementClick {check_enabled: true, check_url: false}`), where the
+// fields with defaults can be left out. A step taking a single argument
+// can also be given the bare value (such as `!PageWait 100`).
+
+/// `LocateMode` enum defines which of the found elements get selected.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(remote = "Self", rename_all = "kebab-case")]
+pub enum LocateMode {
+    /// All the found elements.
+    #[default]
+    All,
+    /// Only the element at given `index`.
+    Index,
+    /// Only the first element.
+    First,
+    /// Only the last element.
+    Last,
+    /// The elements from `start` up to (excluding) `end`, such as `!range [0, 3]`.
+    Range(usize, usize),
+    /// The elements whose visible text contains given text, such as `!filter "English"`.
+    Filter(String),
+}
+
+impl serde::Serialize for LocateMode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LocateMode::serialize(self, serializer)
+    }
+}
+
+impl<'de> TaggedEnum<'de> for LocateMode {
+    fn deserialize_variant<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        LocateMode::deserialize(deserializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LocateMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_tagged(deserializer)
+    }
+}
+
+impl fmt::Display for LocateMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocateMode::All => write!(f, "all"),
+            LocateMode::Index => write!(f, "index"),
+            LocateMode::First => write!(f, "first"),
+            LocateMode::Last => write!(f, "last"),
+            LocateMode::Range(start, end) => write!(f, "range({start}, {end})"),
+            LocateMode::Filter(text) => write!(f, "filter({text:?})"),
+        }
+    }
+}
+
+/// `ScrollMode` enum defines how `PageScroll` interprets its `page_size`.
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum ScrollMode {
+    /// Scroll to the bottom of the page for `page_size` 1.0, or to the top
+    /// of the page for `page_size` -1.0.
+    Full,
+    /// Scroll down by `page_size` pages (up, if negative).
+    #[default]
+    Page,
+    /// Scroll the currently selected element into view (ignoring `page_size`).
+    ToElement,
+    /// Scroll down by `page_size` pixels (up, if negative).
+    Pixels,
+    /// Scroll right by `page_size` pages (left, if negative).
+    Horizontal,
+}
+
+/// Arguments of `PageLocateElements` and `ElementLocateChildren`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct LocateElementsArgs {
+    /// How to find the elements: a CSS selector, or one of the locator
+    /// strategies (such as `{xpath: "//div"}`).
+    #[serde(alias = "css")]
+    #[schemars(schema_with = "locator::locator_schema")]
+    pub locator: Locator,
+    /// Which of the found elements to select (default "all").
+    #[serde(default)]
+    pub mode: LocateMode,
+    /// Index of the element to select in "index" mode (default 0).
+    #[serde(default)]
+    pub index: usize,
+    /// Time to keep polling for the elements to appear (default the
+    /// `timeout_ms` of the workflow).
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Whether to first wait for the DOM to stop changing (default the
+    /// `wait_dom_stable` of the workflow).
+    #[serde(default)]
+    pub wait_dom_stable: Option<bool>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `ElementsLoopThrough`, which can also be given as the bare
+/// list of sub-steps.
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ElementsLoopThroughArgs {
+    pub sub_steps: Vec<WorkflowStep>,
+    /// Name of the list of records, if each iteration is to save its values
+    /// to a new record (see `ValueStore`).
+    #[serde(default)]
+    pub record: Option<String>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `PageLoop`, which can also be given as the bare list of
+/// sub-steps.
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PageLoopArgs {
+    pub sub_steps: Vec<WorkflowStep>,
+    /// Name of the list of records, if each iteration is to save its values
+    /// to a new record (see `ValueStore`).
+    #[serde(default)]
+    pub record: Option<String>,
+    /// Number of iterations after which the loop ends (default unbounded).
+    #[serde(default)]
+    pub max_iterations: Option<usize>,
+    /// Time since the loop started after which no more iterations are
+    /// started (default unbounded).
+    #[serde(default)]
+    pub max_duration_ms: Option<u64>,
+    /// Errors of the sub-steps ending the loop, rather than failing the
+    /// workflow (default `[element_not_found]`).
+    #[serde(default = "loops::default_page_loop_break_on")]
+    pub break_on: Vec<BreakOn>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `Repeat`.
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RepeatArgs {
+    /// Number of times to execute the sub-steps.
+    pub times: usize,
+    pub sub_steps: Vec<WorkflowStep>,
+    /// Name of the list of records, if each iteration is to save its values
+    /// to a new record (see `ValueStore`).
+    #[serde(default)]
+    pub record: Option<String>,
+    /// Errors of the sub-steps ending the loop, rather than failing the
+    /// workflow (default none).
+    #[serde(default)]
+    pub break_on: Vec<BreakOn>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `While`.
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct WhileArgs {
+    /// Condition checked before each iteration: a CSS selector of an element
+    /// expected to exist, or one of the conditions (such as `{js: "..."}`).
+    #[schemars(schema_with = "condition::condition_schema")]
+    pub condition: Condition,
+    pub sub_steps: Vec<WorkflowStep>,
+    /// Name of the list of records, if each iteration is to save its values
+    /// to a new record (see `ValueStore`).
+    #[serde(default)]
+    pub record: Option<String>,
+    /// Number of iterations after which the loop ends (default unbounded).
+    #[serde(default)]
+    pub max_iterations: Option<usize>,
+    /// Time since the loop started after which no more iterations are
+    /// started (default unbounded).
+    #[serde(default)]
+    pub max_duration_ms: Option<u64>,
+    /// Errors of the sub-steps ending the loop, rather than failing the
+    /// workflow (default none).
+    #[serde(default)]
+    pub break_on: Vec<BreakOn>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `BreakIfMissing`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct BreakIfMissingArgs {
+    /// How to find the elements: a CSS selector, or one of the locator strategies.
+    #[schemars(schema_with = "locator::locator_schema")]
+    pub locator: Locator,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `If`.
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct IfArgs {
+    /// Condition to branch on: a CSS selector of an element expected to
+    /// exist, or one of the conditions (such as `{url_matches: "/login$"}`).
+    #[schemars(schema_with = "condition::condition_schema")]
+    pub condition: Condition,
+    /// Sub-steps to execute if the condition holds.
+    pub then: Vec<WorkflowStep>,
+    /// Sub-steps to execute otherwise (default none).
+    #[serde(default, rename = "else")]
+    pub else_steps: Vec<WorkflowStep>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `Try`.
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TryArgs {
+    /// Sub-steps to execute.
+    pub steps: Vec<WorkflowStep>,
+    /// Sub-steps to execute if any of `steps` fails, with the error message
+    /// as `${error}` (default none, i.e. the error is not caught).
+    #[serde(default)]
+    pub catch: Vec<WorkflowStep>,
+    /// Sub-steps to execute in the end, whether `steps` failed or not
+    /// (default none).
+    #[serde(default)]
+    pub finally: Vec<WorkflowStep>,
+    /// Whether to still fail with the error once `catch` is done (default false).
+    #[serde(default)]
+    pub rethrow: bool,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of the steps without arguments of their own (such as
+/// `PageRefresh`), which take `on_error` only.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct NoArgs {
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `ElementSaveHtmlValue`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ElementSaveHtmlValueArgs {
+    /// Name to save the value with.
+    pub name: String,
+    /// Save the inner HTML rather than the outer one (default `false`).
+    #[serde(default)]
+    pub is_inner: bool,
+    /// Transforms of the value, as with `SaveValueArgs`.
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `ElementSaveText`, `PageSaveUrl` and `PageSaveTitle`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct SaveValueArgs {
+    /// Name to save the value with.
+    pub name: String,
+    /// Transforms to clean up the value with before it gets saved, in order
+    /// (such as `[trim, parse_number]`, see `Transform`).
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `ElementSaveAttribute`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ElementSaveAttributeArgs {
+    /// Name to save the value with.
+    pub name: String,
+    /// Name of the HTML attribute, such as `href`.
+    pub attr: String,
+    /// Transforms of the value, as with `SaveValueArgs`.
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `ElementSaveProperty`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ElementSavePropertyArgs {
+    /// Name to save the value with.
+    pub name: String,
+    /// Name of the DOM property, such as `value` or `checked`.
+    pub prop: String,
+    /// Transforms of the value, as with `SaveValueArgs`.
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `ElementSaveCssValue`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ElementSaveCssValueArgs {
+    /// Name to save the value with.
+    pub name: String,
+    /// Name of the CSS property, such as `color`.
+    pub property: String,
+    /// Transforms of the value, as with `SaveValueArgs`.
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `PageOpen`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct PageOpenArgs {
+    pub url: String,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `PageScroll`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct PageScrollArgs {
+    /// "full", "page", "to-element", "pixels" or "horizontal" (default "page").
+    #[serde(default)]
+    pub mode: ScrollMode,
+    /// Number of pages to scroll by (default 1.0).
+    #[serde(default = "default_page_size")]
+    pub page_size: f64,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `PageTakeScreenshot` and `ElementTakeScreenshot`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct TakeScreenshotArgs {
+    /// Prefix of the name of the screenshot file.
+    pub file_prefix: String,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `ExportValues`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ExportValuesArgs {
+    /// Format of the file: "json", "ndjson" or "csv" (default "json").
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Prefix of the name of the file (default none).
+    #[serde(default)]
+    pub file_prefix: String,
+    /// Name of the list of records (such as saved by a loop with `record`)
+    /// to export the rows of (default the records of all the lists).
+    #[serde(default)]
+    pub records: Option<String>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `PageWait`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct PageWaitArgs {
+    pub duration_ms: u64,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `ElementClick`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ElementClickArgs {
+    /// Raise error if the element is not enabled (default `false`).
+    #[serde(default)]
+    pub check_enabled: bool,
+    /// Raise error if the URL didn't change by the click (default `false`).
+    #[serde(default)]
+    pub check_url: bool,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `ElementSendKeys`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ElementSendKeysArgs {
+    pub keys: String,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// `ElementState` enum defines the states `WaitForElement` can wait for.
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum ElementState {
+    /// Any matching element is on the page.
+    #[default]
+    Present,
+    /// Any matching element is displayed.
+    Visible,
+    /// Any matching element is displayed and enabled.
+    Clickable,
+    /// No matching element is displayed (anymore).
+    Gone,
+}
+
+/// Arguments of `WaitForElement`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct WaitForElementArgs {
+    /// How to find the elements: a CSS selector, or one of the locator strategies.
+    #[schemars(schema_with = "locator::locator_schema")]
+    pub locator: Locator,
+    /// State to wait for (default "present").
+    #[serde(default)]
+    pub state: ElementState,
+    /// Time to wait for, before failing (default 10000).
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Time between the checks (default 250).
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `WaitForUrl`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct WaitForUrlArgs {
+    /// Regular expression the URL of the current page must match.
+    pub pattern: String,
+    /// Time to wait for, before failing (default 10000).
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Time between the checks (default 250).
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `WaitForText`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct WaitForTextArgs {
+    /// Text to wait for.
+    pub text: String,
+    /// Elements the text must appear within (default the whole page).
+    #[serde(default)]
+    #[schemars(schema_with = "locator::locator_schema")]
+    pub locator: Option<Locator>,
+    /// Time to wait for, before failing (default 10000).
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Time between the checks (default 250).
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `WaitForJs`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct WaitForJsArgs {
+    /// JavaScript expression to wait for to be truthy, such as `window.appReady`.
+    pub expression: String,
+    /// Time to wait for, before failing (default 10000).
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Time between the checks (default 250).
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `AssertElementCount`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct AssertElementCountArgs {
+    /// How to find the elements: a CSS selector, or one of the locator strategies.
+    #[schemars(schema_with = "locator::locator_schema")]
+    pub locator: Locator,
+    /// Expected number of the found elements.
+    pub count: usize,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `AssertText`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct AssertTextArgs {
+    /// Expected visible text: the exact text, or `!contains`/`!regex` one.
+    #[schemars(schema_with = "assertion::text_match_schema")]
+    pub expected: TextMatch,
+    /// Element to check: the first one found by given locator (default the
+    /// currently selected element).
+    #[serde(default)]
+    #[schemars(schema_with = "locator::locator_schema")]
+    pub locator: Option<Locator>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `AssertAttribute`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct AssertAttributeArgs {
+    /// Name of the attribute, such as `href`.
+    pub name: String,
+    /// Expected value: the exact value, or `!contains`/`!regex` one.
+    #[schemars(schema_with = "assertion::text_match_schema")]
+    pub expected: TextMatch,
+    /// Element to check: the first one found by given locator (default the
+    /// currently selected element).
+    #[serde(default)]
+    #[schemars(schema_with = "locator::locator_schema")]
+    pub locator: Option<Locator>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `AssertUrl` and `AssertTitle`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct AssertPageArgs {
+    /// Expected text: the exact text, or `!contains`/`!regex` one.
+    #[schemars(schema_with = "assertion::text_match_schema")]
+    pub expected: TextMatch,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `AssertVisible`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct AssertVisibleArgs {
+    /// Element to check: the first one found by given locator (default the
+    /// currently selected element).
+    #[serde(default)]
+    #[schemars(schema_with = "locator::locator_schema")]
+    pub locator: Option<Locator>,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+/// Arguments of `AssertValue`.
+#[derive(
+    serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default, Clone, PartialEq,
+)]
+#[serde(deny_unknown_fields)]
+pub struct AssertValueArgs {
+    /// Name the value was saved with (such as by `ElementSaveHtmlValue`).
+    pub name: String,
+    /// Expected value: the exact value, or `!contains`/`!regex` one.
+    #[schemars(schema_with = "assertion::text_match_schema")]
+    pub expected: TextMatch,
+    /// Policy for when the step fails (default the `on_error` of the workflow).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::optional_error_policy_schema")]
+    pub on_error: Option<ErrorPolicy>,
+}
+
+impl From<Vec<WorkflowStep>> for ElementsLoopThroughArgs {
+    fn from(sub_steps: Vec<WorkflowStep>) -> Self {
+        ElementsLoopThroughArgs {
+            sub_steps,
+            record: None,
+            on_error: None,
+        }
+    }
+}
+
+impl From<Vec<WorkflowStep>> for PageLoopArgs {
+    fn from(sub_steps: Vec<WorkflowStep>) -> Self {
+        PageLoopArgs {
+            sub_steps,
+            record: None,
+            max_iterations: None,
+            max_duration_ms: None,
+            break_on: loops::default_page_loop_break_on(),
+            on_error: None,
+        }
+    }
+}
+
+fn default_page_size() -> f64 {
+    1.0
+}
+
+fn default_timeout_ms() -> u64 {
+    10000
+}
+
+fn default_interval_ms() -> u64 {
+    250
+}
+
+/// `TaggedEnum` trait is implemented by the enums (deriving `Deserialize`
+/// with `#[serde(remote = "Self")]`) whose variants can be given either
+/// as a YAML tag (such as `!PageWait 100`), or as a single-entry map (such
+/// as `PageWait: 100`). The latter is the only form a JSON Schema (and so
+/// an editor) can describe.
+pub trait TaggedEnum<'de>: Sized {
+    /// Deserialize the enum, as derived by serde.
+    fn deserialize_variant<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+
+    /// Deserialize the enum given as a plain string, which is the name of
+    /// a unit variant by default.
+    fn from_str<E: de::Error>(value: &str) -> Result<Self, E> {
+        Self::deserialize_variant(value.into_deserializer())
+    }
+}
+
+/// Deserialize an enum given either as a YAML tag, as a single-entry map,
+/// or as the bare name of a unit variant.
+pub fn deserialize_tagged<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TaggedEnum<'de>,
+{
+    deserializer.deserialize_any(TaggedVisitor(PhantomData))
+}
+
+/// Deserialize step arguments given as positional values, named values,
+/// or a single bare value (filling the first of the arguments).
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    deserializer.deserialize_any(ArgsVisitor(PhantomData))
+}
+
+/// Deserialize arguments of a loop step, which are given either as named
+/// values, or as the bare list of sub-steps.
+pub fn deserialize_sub_steps<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + From<Vec<WorkflowStep>>,
+{
+    deserializer.deserialize_any(SubStepsVisitor(PhantomData))
+}
+
+/// Return JSON Schema of step arguments, which are given either as named
+/// values, as positional values, or as a single bare value.
+pub fn args_schema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    let named = T::json_schema(gen);
+    let object = match &named {
+        Schema::Object(SchemaObject {
+            object: Some(object),
+            ..
+        }) => object.clone(),
+        _ => return named,
+    };
+    let names: Vec<&String> = object.properties.keys().collect();
+    let properties: Vec<Schema> = object.properties.values().cloned().collect();
+    // the positional values must include all the required ones
+    let min_items = names
+        .iter()
+        .rposition(|name| object.required.contains(*name))
+        .map_or(0, |index| index + 1);
+    let positional = SchemaObject {
+        instance_type: Some(InstanceType::Array.into()),
+        array: Some(Box::new(ArrayValidation {
+            items: Some(SingleOrVec::Vec(properties.clone())),
+            min_items: Some(min_items as u32),
+            max_items: Some(properties.len() as u32),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    let mut any_of = vec![named.clone(), positional.into()];
+    if min_items <= 1 {
+        any_of.extend(properties.into_iter().next());
+    }
+    SchemaObject {
+        subschemas: Some(Box::new(SubschemaValidation {
+            any_of: Some(any_of),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Return JSON Schema of an enum given either as a plain string (taken as
+/// one of its variants, see `TaggedEnum::from_str`), or as any of its variants.
+pub fn string_or_schema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    let string = SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        ..Default::default()
+    };
+    SchemaObject {
+        subschemas: Some(Box::new(SubschemaValidation {
+            any_of: Some(vec![string.into(), gen.subschema_for::<T>()]),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Return JSON Schema of arguments of a loop step, which are given either
+/// as named values, or as the bare list of sub-steps.
+pub fn sub_steps_schema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    SchemaObject {
+        subschemas: Some(Box::new(SubschemaValidation {
+            any_of: Some(vec![
+                T::json_schema(gen),
+                gen.subschema_for::<Vec<WorkflowStep>>(),
+            ]),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+struct TaggedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: TaggedEnum<'de>> Visitor<'de> for TaggedVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a YAML tag starting with '!', or a map with the variant name as its only key"
+        )
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<T, A::Error> {
+        T::deserialize_variant(de::value::EnumAccessDeserializer::new(data))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<T, A::Error> {
+        T::deserialize_variant(de::value::MapAccessDeserializer::new(map))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        T::from_str(value)
+    }
+}
+
+struct ArgsVisitor<T>(PhantomData<T>);
+
+impl<T> ArgsVisitor<T> {
+    fn single<'de, V, E>(value: V) -> Result<T, E>
+    where
+        T: Deserialize<'de>,
+        V: IntoDeserializer<'de, E>,
+        E: de::Error,
+    {
+        T::deserialize(de::value::SeqDeserializer::new(std::iter::once(value)))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for ArgsVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a list of positional arguments, a map of named arguments, or a single argument"
+        )
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<T, A::Error> {
+        T::deserialize(de::value::SeqAccessDeserializer::new(seq))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<T, A::Error> {
+        T::deserialize(de::value::MapAccessDeserializer::new(map))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<T, E> {
+        // no arguments at all, so all of them take their defaults
+        T::deserialize(de::value::MapDeserializer::new(std::iter::empty::<(
+            String,
+            String,
+        )>()))
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<T, E> {
+        Self::single(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        Self::single(value)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        Self::single(value)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<T, E> {
+        Self::single(value)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        Self::single(value.to_string())
+    }
+}
+
+struct SubStepsVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for SubStepsVisitor<T>
+where
+    T: Deserialize<'de> + From<Vec<WorkflowStep>>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of sub-steps, or a map of named arguments")
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<T, A::Error> {
+        Vec::<WorkflowStep>::deserialize(de::value::SeqAccessDeserializer::new(seq)).map(T::from)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<T, A::Error> {
+        T::deserialize(de::value::MapAccessDeserializer::new(map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wf::workflow::Workflow;
+
+    fn parse_steps(steps: &str) -> Vec<WorkflowStep> {
+        let content = format!("name: \"test\"\nsteps:\n{steps}");
+        let wf: Workflow = serde_yaml::from_str(&content).expect("Failed to parse workflow");
+        wf.steps
+    }
+
+    #[test]
+    fn positional_and_named_args_are_equivalent() {
+        let steps = parse_steps(
+            r#"
+              - !ElementClick [true, false]
+              - !ElementClick {check_enabled: true, check_url: false}
+              - !PageLocateElements ["body div", "index", 2]
+              - !PageLocateElements {locator: "body div", index: 2, mode: "index"}
+            "#,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::ElementClick(a), WorkflowStep::ElementClick(b), WorkflowStep::PageLocateElements(c), WorkflowStep::PageLocateElements(d)] =>
+            {
+                assert_eq!(a, b);
+                assert!(a.check_enabled && !a.check_url);
+                assert_eq!(c, d);
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn omitted_args_take_defaults() {
+        let steps = parse_steps(
+            r#"
+              - !PageLocateElements {css: "a"}
+              - !PageLocateElements ["a"]
+              - !ElementClick
+              - !PageScroll "page"
+              - !PageWait 100
+            "#,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::PageLocateElements(a), WorkflowStep::PageLocateElements(b), WorkflowStep::ElementClick(click), WorkflowStep::PageScroll(scroll), WorkflowStep::PageWait(wait)] =>
+            {
+                assert_eq!((&a.mode, a.index), (&LocateMode::All, 0));
+                assert_eq!((a.timeout_ms, a.wait_dom_stable), (None, None));
+                assert_eq!(a, b);
+                assert!(!click.check_enabled && !click.check_url);
+                assert_eq!(scroll.page_size, 1.0);
+                assert_eq!(wait.duration_ms, 100);
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn extraction_args() {
+        let steps = parse_steps(
+            r#"
+              - !ElementSaveText "heading"
+              - !ElementSaveAttribute ["link", "href"]
+              - !ElementSaveProperty {name: "query", prop: "value"}
+              - !ElementSaveCssValue ["color", "color"]
+              - !PageSaveUrl "url"
+            "#,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::ElementSaveText(text), WorkflowStep::ElementSaveAttribute(attr), WorkflowStep::ElementSaveProperty(prop), WorkflowStep::ElementSaveCssValue(css), WorkflowStep::PageSaveUrl(url)] =>
+            {
+                assert_eq!(text.name, "heading");
+                assert_eq!((attr.name.as_str(), attr.attr.as_str()), ("link", "href"));
+                assert_eq!((prop.name.as_str(), prop.prop.as_str()), ("query", "value"));
+                assert_eq!(css.property, "color");
+                assert_eq!(url.name, "url");
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bounded_loop_args() {
+        let steps = parse_steps(
+            r#"
+              - !PageLoop
+                sub_steps:
+                  - !PageBack
+                max_iterations: 20
+                max_duration_ms: 60000
+              - !Repeat
+                - 3
+                - - !PageWait 100
+              - !While
+                condition: !js "window.hasMore"
+                sub_steps:
+                  - !PageScroll ["page", 1.0]
+                max_iterations: 10
+            "#,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::PageLoop(a), WorkflowStep::Repeat(b), WorkflowStep::While(c)] => {
+                assert_eq!(
+                    (a.max_iterations, a.max_duration_ms),
+                    (Some(20), Some(60000))
+                );
+                assert_eq!((b.times, b.sub_steps.len(), &b.record), (3, 1, &None));
+                assert_eq!(c.condition, Condition::Js(String::from("window.hasMore")));
+                assert_eq!((c.max_iterations, c.max_duration_ms), (Some(10), None));
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn loop_break_on_args() {
+        let steps = parse_steps(
+            r#"
+              - !PageLoop
+                - !BreakIfMissing "a.next"
+                - !PageBack
+              - !PageLoop
+                sub_steps:
+                  - !PageBack
+                break_on: []
+              - !Repeat
+                times: 5
+                sub_steps:
+                  - !PageWait 100
+                break_on: [timeout, assertion]
+            "#,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::PageLoop(a), WorkflowStep::PageLoop(b), WorkflowStep::Repeat(c)] => {
+                assert_eq!(a.break_on, vec![BreakOn::ElementNotFound]);
+                assert!(matches!(
+                    &a.sub_steps[0],
+                    WorkflowStep::BreakIfMissing(BreakIfMissingArgs {
+                        locator: Locator::Css(selector), .. }) if selector == "a.next"
+                ));
+                assert!(b.break_on.is_empty());
+                assert_eq!(c.break_on, vec![BreakOn::Timeout, BreakOn::Assertion]);
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn try_args_with_catch_and_finally() {
+        let steps = parse_steps(
+            r#"
+              - !Try
+                steps:
+                  - !PageOpen "https://example.com/login"
+                catch:
+                  - !PageTakeScreenshot "login_failed"
+                  - !PageOpen "https://example.com/report?error=${error}"
+                finally:
+                  - !PageOpen "https://example.com/logout"
+                rethrow: true
+              - !Try [[!PageWait 100]]
+            "#,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::Try(a), WorkflowStep::Try(b)] => {
+                assert_eq!(
+                    (a.steps.len(), a.catch.len(), a.finally.len(), a.rethrow),
+                    (1, 2, 1, true)
+                );
+                assert_eq!(
+                    (b.steps.len(), b.catch.len(), b.finally.len(), b.rethrow),
+                    (1, 0, 0, false)
+                );
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn on_error_args() {
+        let content = r#"
+          name: "products"
+          on_error: !retry {times: 2, backoff_ms: 500}
+          steps:
+            - !ElementSaveText {name: "price", on_error: skip_iteration}
+            - !PageWait {duration_ms: 100, on_error: continue}
+            - !ElementClick
+            - !PageRefresh {on_error: !retry {times: 2}}
+            - PageBack
+            - !Repeat
+              times: 3
+              sub_steps:
+                - !ElementClickNewWindow
+                - !PageBackWindow
+              on_error: continue
+        "#;
+        let wf: Workflow = serde_yaml::from_str(content).expect("Failed to parse workflow");
+
+        assert_eq!(
+            wf.on_error,
+            ErrorPolicy::Retry {
+                times: 2,
+                backoff_ms: 500
+            }
+        );
+        let policies: Vec<Option<&ErrorPolicy>> =
+            wf.steps.iter().map(WorkflowStep::on_error).collect();
+        assert_eq!(
+            policies,
+            vec![
+                Some(&ErrorPolicy::SkipIteration),
+                Some(&ErrorPolicy::Continue),
+                None,
+                Some(&ErrorPolicy::Retry {
+                    times: 2,
+                    backoff_ms: 0
+                }),
+                None,
+                Some(&ErrorPolicy::Continue),
+            ]
+        );
+        assert_eq!(
+            wf.steps[0].to_string(),
+            "ElementSaveText with name=price (on error: skip_iteration)"
+        );
+    }
+
+    #[test]
+    fn if_args_with_branches() {
+        let steps = parse_steps(
+            r##"
+              - !If
+                condition: "#cookie-banner"
+                then:
+                  - !PageWait 100
+                else:
+                  - !PageRefresh
+                  - !PageWait 100
+              - !If
+                - !url_matches "/login$"
+                - - !PageBack
+            "##,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::If(a), WorkflowStep::If(b)] => {
+                assert_eq!(
+                    a.condition,
+                    Condition::ElementExists(Locator::Css(String::from("#cookie-banner")))
+                );
+                assert_eq!((a.then.len(), a.else_steps.len()), (1, 2));
+                assert_eq!(b.condition, Condition::UrlMatches(String::from("/login$")));
+                assert!(matches!(b.then[..], [WorkflowStep::PageBack(..)]));
+                assert!(b.else_steps.is_empty());
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn export_values_args() {
+        let steps = parse_steps(
+            r#"
+              - !ExportValues csv
+              - !ExportValues [ndjson, "products", "products"]
+              - !ExportValues {}
+            "#,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::ExportValues(csv), WorkflowStep::ExportValues(ndjson), WorkflowStep::ExportValues(json)] =>
+            {
+                assert_eq!(csv.format, ExportFormat::Csv);
+                assert_eq!(
+                    (ndjson.file_prefix.as_str(), ndjson.records.as_deref()),
+                    ("products", Some("products"))
+                );
+                assert_eq!(json, &ExportValuesArgs::default());
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn loop_sub_steps_as_list_or_named() {
+        let steps = parse_steps(
+            r#"
+              - !ElementsLoopThrough
+                - !PageWait 100
+                - !ElementPop
+              - !PageLoop
+                sub_steps:
+                  - !PageBack
+                record: "pages"
+            "#,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::ElementsLoopThrough(a), WorkflowStep::PageLoop(b)] => {
+                assert_eq!((a.sub_steps.len(), &a.record), (2, &None));
+                assert!(matches!(b.sub_steps[..], [WorkflowStep::PageBack(..)]));
+                assert_eq!(b.record.as_deref(), Some("pages"));
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn steps_as_tags_or_maps_are_equivalent() {
+        let steps = parse_steps(
+            r#"
+              - !PageLocateElements ["li", !range [1, 3]]
+              - PageLocateElements: {css: "li", mode: {range: [1, 3]}}
+              - !ElementPop
+              - ElementPop
+              - ElementsLoopThrough:
+                  - PageWait: 100
+                  - ElementPop:
+            "#,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::PageLocateElements(a), WorkflowStep::PageLocateElements(b), WorkflowStep::ElementPop(..), WorkflowStep::ElementPop(..), WorkflowStep::ElementsLoopThrough(c)] =>
+            {
+                assert_eq!(a, b);
+                assert!(matches!(
+                    c.sub_steps[..],
+                    [WorkflowStep::PageWait(_), WorkflowStep::ElementPop(..)]
+                ));
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn typed_modes() {
+        let steps = parse_steps(
+            r#"
+              - !PageLocateElements ["li", !range [1, 3]]
+              - !PageLocateElements {css: "li", mode: !filter "English"}
+              - !PageLocateElements {css: "li", mode: "last"}
+              - !PageScroll ["to-element"]
+            "#,
+        );
+
+        match &steps[..] {
+            [WorkflowStep::PageLocateElements(a), WorkflowStep::PageLocateElements(b), WorkflowStep::PageLocateElements(c), WorkflowStep::PageScroll(d)] =>
+            {
+                assert_eq!(a.mode, LocateMode::Range(1, 3));
+                assert_eq!(b.mode, LocateMode::Filter(String::from("English")));
+                assert_eq!(c.mode, LocateMode::Last);
+                assert_eq!(d.mode, ScrollMode::ToElement);
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_and_missing_args_are_rejected() {
+        for steps in [
+            "  - !ElementClick {check_enabled: true, check_urls: false}\n",
+            "  - !PageLocateElements {mode: \"all\"}\n",
+            "  - !PageWait\n",
+            "  - !PageLocateElements [\"a\", \"alll\", 0]\n",
+            "  - !PageScroll [\"ful\", 1.0]\n",
+            "  - {PageWait: 100, PageBack: null}\n",
+        ] {
+            let content = format!("name: \"test\"\nsteps:\n{steps}");
+            let result: Result<Workflow, _> = serde_yaml::from_str(&content);
+            assert!(result.is_err(), "Unexpectedly parsed: {steps}");
+        }
+    }
+}
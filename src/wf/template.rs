@@ -0,0 +1,223 @@
+use crate::error::{SquirrelError, StepPath};
+use crate::wf::value_store::{self, ValueStore};
+use crate::wf::workflow_step::WorkflowStep;
+use std::collections::BTreeMap;
+
+// Templating of the step arguments.
+//
+// Any string argument of a step (such as a URL, a selector or keys) can
+// refer to variables as `${name}`, which are replaced right before the step
+// is executed, so that a value saved by an earlier step (such as by
+// `ElementSaveHtmlValue`) can be used by a later one. A literal `${` is
+// written as `$${`.
+
+/// Return the value of the variable with given name, looked up (in order)
+/// in the scoped values (such as the `error` caught by `Try`), in the
+/// overrides (such as given by `--var`), in the values saved so far (see
+/// `ValueStore::get`), and in the `vars` of the workflow, so that a scraped
+/// value can't silently override what the operator asked for.
+pub fn lookup(
+    name: &str,
+    value_store: &ValueStore,
+    overrides: &BTreeMap<String, String>,
+    vars: &BTreeMap<String, String>,
+) -> Option<String> {
+    value_store
+        .get_scoped(name)
+        .map(value_store::to_text)
+        .or_else(|| overrides.get(name).cloned())
+        .or_else(|| value_store.get_text(name))
+        .or_else(|| vars.get(name).cloned())
+}
+
+/// Replace the `${name}` references within given text with the values of
+/// the variables, or return an error for an undefined (or unclosed) one.
+pub fn interpolate(text: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            result.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = rest.strip_prefix("${") {
+            let end = reference
+                .find('}')
+                .ok_or_else(|| format!("Unclosed variable reference in {text:?}"))?;
+            let name = reference[..end].trim();
+            let value = lookup(name).ok_or_else(|| format!("Undefined variable ${{{name}}}"))?;
+            result.push_str(&value);
+            rest = &reference[end + 1..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Interpolate all the strings within given value, and return `true` if
+/// any of them changed.
+fn render_value(
+    value: &mut serde_yaml::Value,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<bool, String> {
+    match value {
+        serde_yaml::Value::String(text) if text.contains('$') => {
+            let rendered = interpolate(text, lookup)?;
+            let changed = rendered != *text;
+            *text = rendered;
+            Ok(changed)
+        }
+        serde_yaml::Value::Sequence(values) => {
+            let mut changed = false;
+            for value in values {
+                changed |= render_value(value, lookup)?;
+            }
+            Ok(changed)
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut changed = false;
+            for (_, value) in mapping.iter_mut() {
+                changed |= render_value(value, lookup)?;
+            }
+            Ok(changed)
+        }
+        serde_yaml::Value::Tagged(tagged) => render_value(&mut tagged.value, lookup),
+        _ => Ok(false),
+    }
+}
+
+/// Return given step with the variables within its string arguments
+/// replaced, or `None` if it doesn't refer to any variable.
+///
+/// The sub-steps of the loops (and of `If` and `Try`) are left
+/// as they are, as they get rendered when they are executed (with the
+/// values saved by then).
+pub fn render(
+    step: &WorkflowStep,
+    lookup: impl Fn(&str) -> Option<String>,
+    path: &StepPath,
+) -> Result<Option<WorkflowStep>, SquirrelError> {
+    if step.is_compound() {
+        return Ok(None);
+    }
+    render_args(step, lookup, path)
+}
+
+/// Return given arguments (such as the condition of `If`) with the
+/// variables within their strings replaced, or `None` if they don't refer
+/// to any variable.
+pub fn render_args<T: serde::Serialize + serde::de::DeserializeOwned>(
+    args: &T,
+    lookup: impl Fn(&str) -> Option<String>,
+    path: &StepPath,
+) -> Result<Option<T>, SquirrelError> {
+    let internal = |message: String| SquirrelError::Internal {
+        path: path.clone(),
+        message,
+    };
+    let mut value = serde_yaml::to_value(args).map_err(|error| internal(error.to_string()))?;
+    let changed =
+        render_value(&mut value, &lookup).map_err(|message| SquirrelError::Validation {
+            path: path.clone(),
+            message,
+        })?;
+    if !changed {
+        return Ok(None);
+    }
+    let args = serde_yaml::from_value(value).map_err(|error| internal(error.to_string()))?;
+    Ok(Some(args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wf::step_args;
+
+    fn vars() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            (String::from("host"), String::from("www.wikipedia.org")),
+            (String::from("term"), String::from("rust")),
+        ])
+    }
+
+    #[test]
+    fn interpolate_references() {
+        let vars = vars();
+        let lookup = |name: &str| vars.get(name).cloned();
+
+        assert_eq!(
+            interpolate("https://${host}/?q=${ term }&p=$1", lookup),
+            Ok(String::from("https://www.wikipedia.org/?q=rust&p=$1"))
+        );
+        assert_eq!(interpolate("$${host}", lookup), Ok(String::from("${host}")));
+        assert_eq!(
+            interpolate("${missing}", lookup),
+            Err(String::from("Undefined variable ${missing}"))
+        );
+        assert!(interpolate("${host", lookup).is_err());
+    }
+
+    #[test]
+    fn lookup_prefers_overrides_and_saved_values() {
+        let mut store = ValueStore::new();
+        store.save("link", serde_json::json!("https://old.example.com"));
+        store.save("link", serde_json::json!("https://new.example.com"));
+        store.save("term", serde_json::json!("scraped"));
+        store.save("host", serde_json::json!("www.example.com"));
+        let overrides = BTreeMap::from([(String::from("term"), String::from("serde"))]);
+        let vars = vars();
+        let lookup = |name: &str| lookup(name, &store, &overrides, &vars);
+
+        assert_eq!(lookup("link").as_deref(), Some("https://new.example.com"));
+        assert_eq!(lookup("term").as_deref(), Some("serde"));
+        assert_eq!(lookup("host").as_deref(), Some("www.example.com"));
+        assert_eq!(lookup("missing"), None);
+    }
+
+    #[test]
+    fn lookup_prefers_scoped_values() {
+        let mut store = ValueStore::new();
+        store.begin_scope("error", serde_json::json!("Element not found"));
+        let overrides = BTreeMap::from([(String::from("error"), String::from("none"))]);
+
+        let value = lookup("error", &store, &overrides, &vars());
+
+        assert_eq!(value.as_deref(), Some("Element not found"));
+    }
+
+    #[test]
+    fn render_string_arguments() {
+        let vars = vars();
+        let lookup = |name: &str| vars.get(name).cloned();
+        let steps: Vec<WorkflowStep> = serde_yaml::from_str(
+            r#"
+              - !PageOpen "https://${host}/"
+              - !PageLocateElements [!xpath "//a[text()='${term}']", "first"]
+              - !PageWait 100
+            "#,
+        )
+        .expect("Failed to parse steps");
+
+        let rendered: Vec<Option<WorkflowStep>> = steps
+            .iter()
+            .map(|step| render(step, lookup, &StepPath::default()).unwrap())
+            .collect();
+
+        match &rendered[..] {
+            [Some(WorkflowStep::PageOpen(open)), Some(WorkflowStep::PageLocateElements(locate)), None] =>
+            {
+                assert_eq!(open.url, "https://www.wikipedia.org/");
+                assert_eq!(
+                    locate.locator,
+                    crate::wf::locator::Locator::Xpath(String::from("//a[text()='rust']"))
+                );
+                assert_eq!(locate.mode, step_args::LocateMode::First);
+            }
+            other => panic!("Unexpected steps: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use crate::error::{SquirrelError, StepPath};
+use crate::wf::step_args::{self, TaggedEnum};
+use serde::de::Deserializer;
+use serde_json::Value;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// `Transform` enum defines the ways to clean up a value before it gets
+/// saved, given (in order) as `transforms` of the save steps. The ones
+/// without arguments are given by name (such as `trim`), while the other
+/// ones are given either as a YAML tag (such as `!capture "(\d+)"`), or as a
+/// single-entry map (such as `{capture: "(\d+)"}`).
+///
+/// A list of values (such as by `split`) gets transformed value by value,
+/// while `null` (such as of a missing attribute) is left as it is, unless
+/// replaced by `default_if_empty`.
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone, PartialEq)]
+#[serde(remote = "Self", rename_all = "snake_case")]
+pub enum Transform {
+    /// Remove the leading and trailing whitespace.
+    Trim,
+    /// Convert to lowercase.
+    Lowercase,
+    /// Keep the first capture group (or else the whole match) of given
+    /// regular expression, such as `!capture "(\d+) reviews"`.
+    Capture(String),
+    /// Replace all the matches of given regular expression with given
+    /// replacement (which can refer to the groups as `$1`), such as
+    /// `!replace ["\s+", " "]`.
+    Replace(String, String),
+    /// Parse the first number in the text, ignoring the text around it and
+    /// its thousands separators (such as "$1,234.50" or "4.5 stars"). A
+    /// number with a decimal comma (such as "12,50" or "1.234,50") is not
+    /// supported, and fails rather than being taken for another number.
+    ParseNumber,
+    /// Parse a date (and time) in given `chrono` format (such as
+    /// `!parse_date "%d %B %Y"`) into ISO-8601 (such as "2024-03-01").
+    ParseDate(String),
+    /// Split into a list of values by given separator, such as `!split ","`.
+    Split(String),
+    /// Replace an empty value (or `null`) with given one.
+    DefaultIfEmpty(String),
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transform::Trim => write!(f, "trim"),
+            Transform::Lowercase => write!(f, "lowercase"),
+            Transform::Capture(pattern) => write!(f, "capture({pattern:?})"),
+            Transform::Replace(pattern, replacement) => {
+                write!(f, "replace({pattern:?}, {replacement:?})")
+            }
+            Transform::ParseNumber => write!(f, "parse_number"),
+            Transform::ParseDate(format) => write!(f, "parse_date({format:?})"),
+            Transform::Split(separator) => write!(f, "split({separator:?})"),
+            Transform::DefaultIfEmpty(default) => write!(f, "default_if_empty({default:?})"),
+        }
+    }
+}
+
+impl serde::Serialize for Transform {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Transform::serialize(self, serializer)
+    }
+}
+
+impl<'de> TaggedEnum<'de> for Transform {
+    fn deserialize_variant<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Transform::deserialize(deserializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Transform {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        step_args::deserialize_tagged(deserializer)
+    }
+}
+
+impl Transform {
+    /// Return the regular expression of the transform (if any), or an
+    /// error if it is invalid.
+    pub fn regex(&self) -> Result<Option<regex::Regex>, regex::Error> {
+        match self {
+            Transform::Capture(pattern) | Transform::Replace(pattern, _) => {
+                regex::Regex::new(pattern).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Return the transformed value (using given regular expression of the
+    /// transform, if any), or an error message.
+    fn apply(&self, value: Value, regex: Option<&regex::Regex>) -> Result<Value, String> {
+        let text = match value {
+            Value::Array(values) => {
+                if let (Transform::DefaultIfEmpty(default), true) = (self, values.is_empty()) {
+                    return Ok(Value::String(default.clone()));
+                }
+                let values: Result<Vec<Value>, String> = values
+                    .into_iter()
+                    .map(|value| self.apply(value, regex))
+                    .collect();
+                return values.map(Value::Array);
+            }
+            Value::String(text) => text,
+            Value::Null => {
+                return Ok(match self {
+                    Transform::DefaultIfEmpty(default) => Value::String(default.clone()),
+                    _ => Value::Null,
+                })
+            }
+            // numbers and such are transformed as text
+            value => value.to_string(),
+        };
+        let value = match self {
+            Transform::Trim => Value::String(text.trim().to_string()),
+            Transform::Lowercase => Value::String(text.to_lowercase()),
+            Transform::Capture(_) => {
+                let captures = regex
+                    .and_then(|regex| regex.captures(&text))
+                    .ok_or_else(|| format!("No match of {self} in {text:?}"))?;
+                let capture = captures.get(1).or_else(|| captures.get(0));
+                Value::String(capture.map_or("", |capture| capture.as_str()).to_string())
+            }
+            Transform::Replace(_, replacement) => {
+                let text = match regex {
+                    Some(regex) => regex.replace_all(&text, replacement.as_str()).to_string(),
+                    None => text,
+                };
+                Value::String(text)
+            }
+            Transform::ParseNumber => parse_number(&text)?,
+            Transform::ParseDate(format) => Value::String(parse_date(&text, format)?),
+            Transform::Split(separator) => Value::Array(
+                text.split(separator.as_str())
+                    .map(|part| Value::String(part.to_string()))
+                    .collect(),
+            ),
+            Transform::DefaultIfEmpty(default) if text.is_empty() => Value::String(default.clone()),
+            Transform::DefaultIfEmpty(_) => Value::String(text),
+        };
+        Ok(value)
+    }
+}
+
+/// Return given value transformed by given transforms, in order.
+pub fn apply_all(
+    transforms: &[Transform],
+    mut value: Value,
+    path: &StepPath,
+) -> Result<Value, SquirrelError> {
+    for transform in transforms {
+        let error = |message: String| SquirrelError::Transform {
+            path: path.clone(),
+            message: format!("{transform}: {message}"),
+        };
+        let regex = transform.regex().map_err(|e| error(e.to_string()))?;
+        value = transform.apply(value, regex.as_ref()).map_err(error)?;
+    }
+    Ok(value)
+}
+
+/// Parse the first number out of given text, as an integer if it has no
+/// fraction.
+fn parse_number(text: &str) -> Result<Value, String> {
+    static NUMBER: OnceLock<regex::Regex> = OnceLock::new();
+    let number_regex =
+        NUMBER.get_or_init(|| regex::Regex::new(r"-?\d+(,\d+)*(\.\d+)?").expect("Invalid regex"));
+    let found = number_regex
+        .find(text)
+        .ok_or_else(|| format!("No number in {text:?}"))?;
+    // A comma is a thousands separator only before groups of 3 digits, or
+    // else it may be the decimal point (such as "12,50"), as may be a point
+    // followed by a comma (such as "1.234,50")
+    let integer_part = found.as_str().split('.').next().unwrap_or_default();
+    let mut groups = integer_part.split(',');
+    let first_group = groups.next().unwrap_or_default().trim_start_matches('-');
+    let mut rest = text[found.end()..].chars();
+    if (integer_part.contains(',') && first_group.len() > 3)
+        || groups.any(|group| group.len() != 3)
+        || matches!(rest.next(), Some('.' | ',')) && rest.next().is_some_and(|c| c.is_ascii_digit())
+    {
+        return Err(format!("Ambiguous number in {text:?}"));
+    }
+    let number = found.as_str().replace(',', "");
+    if let Ok(integer) = number.parse::<i64>() {
+        return Ok(integer.into());
+    }
+    number
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map(Value::Number)
+        .ok_or_else(|| format!("Failed to parse a number from {text:?}"))
+}
+
+/// Parse given text in given `chrono` format, and return it in ISO-8601,
+/// with as much as the format provides (a date, a date and time, or a date
+/// and time with offset).
+fn parse_date(text: &str, format: &str) -> Result<String, String> {
+    let text = text.trim();
+    if let Ok(date_time) = chrono::DateTime::parse_from_str(text, format) {
+        return Ok(date_time.to_rfc3339());
+    }
+    if let Ok(date_time) = chrono::NaiveDateTime::parse_from_str(text, format) {
+        return Ok(date_time.format("%Y-%m-%dT%H:%M:%S").to_string());
+    }
+    chrono::NaiveDate::parse_from_str(text, format)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .map_err(|error| format!("Failed to parse a date from {text:?}: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn transforms(content: &str) -> Vec<Transform> {
+        serde_yaml::from_str(content).expect("Failed to parse transforms")
+    }
+
+    #[test]
+    fn transform_forms() {
+        let transforms = transforms(
+            r#"["trim", !capture "(\\d+)", {replace: [",", ""]}, parse_number, !default_if_empty "n/a"]"#,
+        );
+
+        assert_eq!(
+            transforms,
+            vec![
+                Transform::Trim,
+                Transform::Capture(String::from("(\\d+)")),
+                Transform::Replace(String::from(","), String::from("")),
+                Transform::ParseNumber,
+                Transform::DefaultIfEmpty(String::from("n/a")),
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_transforms_in_order() {
+        let path = StepPath::default();
+        let apply = |content: &str, value: Value| {
+            apply_all(&transforms(content), value, &path).expect("Failed to transform")
+        };
+
+        assert_eq!(
+            apply(r#"[trim, lowercase]"#, json!("  Hello World ")),
+            json!("hello world")
+        );
+        assert_eq!(
+            apply(
+                r#"[!capture "(\\d+) reviews"]"#,
+                json!("4.5 stars (120 reviews)")
+            ),
+            json!("120")
+        );
+        assert_eq!(
+            apply(r#"[!replace ["\\s+", " "]]"#, json!("a \n  b")),
+            json!("a b")
+        );
+        assert_eq!(apply("[parse_number]", json!("$1,234.50")), json!(1234.5));
+        assert_eq!(apply("[parse_number]", json!("-42 items")), json!(-42));
+        assert_eq!(
+            apply("[parse_number]", json!("4.5 stars (120 reviews)")),
+            json!(4.5)
+        );
+        assert_eq!(apply("[parse_number]", json!("Page 2 of 10")), json!(2));
+        assert_eq!(
+            apply("[parse_number]", json!("1,234,567 views")),
+            json!(1234567)
+        );
+        for text in ["1.234,50", "12,50", "€12,50", "1,5", "n/a"] {
+            let result = apply_all(&transforms("[parse_number]"), json!(text), &path);
+            assert!(matches!(result, Err(SquirrelError::Transform { .. })));
+        }
+        assert_eq!(
+            apply(r#"[split: ",", trim, parse_number]"#, json!("1, 2,3")),
+            json!([1, 2, 3])
+        );
+        assert_eq!(
+            apply(r#"[!default_if_empty "n/a"]"#, Value::Null),
+            json!("n/a")
+        );
+        assert_eq!(apply("[trim]", Value::Null), Value::Null);
+    }
+
+    #[test]
+    fn parse_dates_into_iso_8601() {
+        let path = StepPath::default();
+        let apply = |transform: Transform, text: &str| {
+            apply_all(&[transform], json!(text), &path).expect("Failed to transform")
+        };
+
+        assert_eq!(
+            apply(
+                Transform::ParseDate(String::from("%d %B %Y")),
+                "1 March 2024"
+            ),
+            json!("2024-03-01")
+        );
+        assert_eq!(
+            apply(
+                Transform::ParseDate(String::from("%d/%m/%Y %H:%M")),
+                "01/03/2024 09:30"
+            ),
+            json!("2024-03-01T09:30:00")
+        );
+        let result = apply_all(
+            &[Transform::ParseDate(String::from("%Y-%m-%d"))],
+            json!("yesterday"),
+            &path,
+        );
+        assert!(matches!(result, Err(SquirrelError::Transform { .. })));
+    }
+}
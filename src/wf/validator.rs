@@ -0,0 +1,539 @@
+use crate::error::{SquirrelError, StepPath};
+use crate::wf::assertion::TextMatch;
+use crate::wf::error_policy::ErrorPolicy;
+use crate::wf::step_args;
+use crate::wf::transform::Transform;
+use crate::wf::workflow::Workflow;
+use crate::wf::workflow_step::WorkflowStep;
+
+/// `State` struct tracks what the steps validated so far would leave
+/// behind at runtime.
+#[derive(Default)]
+struct State {
+    /// Number of element collections on `current_elements_stack`.
+    stack_size: usize,
+    /// Paths of the `ElementClickNewWindow` steps not yet paired with `PageBackWindow`.
+    open_windows: Vec<StepPath>,
+    /// Number of loops enclosing the step.
+    loop_depth: usize,
+    violations: Vec<SquirrelError>,
+}
+
+impl State {
+    fn violation(&mut self, path: &StepPath, message: String) {
+        self.violations.push(SquirrelError::Validation {
+            path: path.clone(),
+            message,
+        });
+    }
+
+    /// Check that there are located elements for the element step at given path.
+    fn require_elements(&mut self, step: &WorkflowStep, path: &StepPath) {
+        if self.stack_size == 0 {
+            self.violation(
+                path,
+                format!("`{step}` requires located elements (such as by a preceding `PageLocateElements`)"),
+            );
+        }
+    }
+
+    /// Validate given sub-steps of a loop.
+    fn validate_loop(&mut self, sub_steps: &[WorkflowStep], path: &StepPath) {
+        self.loop_depth += 1;
+        validate_steps(sub_steps, path, self);
+        self.loop_depth -= 1;
+    }
+}
+
+/// Walk the workflow tree (without starting the browser), and return the
+/// violations of the element-stack discipline and of the step arguments,
+/// each along with the path of the offending step.
+pub fn validate(wf: &Workflow) -> Vec<SquirrelError> {
+    let mut state = State::default();
+    validate_steps(&wf.steps, &StepPath::default(), &mut state);
+    state.violations
+}
+
+fn validate_steps(steps: &[WorkflowStep], parent_path: &StepPath, state: &mut State) {
+    let open_windows = state.open_windows.len();
+    for (index, step) in steps.iter().enumerate() {
+        validate_step(step, &parent_path.child(index), state);
+    }
+    // The windows opened by these steps must also be closed by them
+    while state.open_windows.len() > open_windows {
+        let path = state.open_windows.pop().unwrap_or_default();
+        state.violation(
+            &path,
+            "`ElementClickNewWindow` always needs to be paired with `PageBackWindow`".to_string(),
+        );
+    }
+}
+
+fn validate_step(step: &WorkflowStep, path: &StepPath, state: &mut State) {
+    for transform in transforms_of(step) {
+        if let Err(error) = transform.regex() {
+            state.violation(path, format!("Invalid pattern of {transform}: {error}"));
+        }
+    }
+    if interval_ms_of(step) == Some(0) {
+        state.violation(path, "`interval_ms` must be greater than 0".to_string());
+    }
+    if let (Some(policy @ ErrorPolicy::SkipIteration), 0) = (step.on_error(), state.loop_depth) {
+        state.violation(
+            path,
+            format!("`{policy}` of `{step}` must be within a loop"),
+        );
+    }
+    match step {
+        WorkflowStep::PageLocateElements(step_args::LocateElementsArgs { mode, .. })
+        | WorkflowStep::ElementLocateChildren(step_args::LocateElementsArgs { mode, .. }) => {
+            if let WorkflowStep::ElementLocateChildren(..) = step {
+                state.require_elements(step, path);
+            }
+            if let step_args::LocateMode::Range(start, end) = mode {
+                if start >= end {
+                    state.violation(path, format!("Empty range {start}..{end}"));
+                }
+            }
+            state.stack_size += 1;
+        }
+        WorkflowStep::ElementsLoopThrough(step_args::ElementsLoopThroughArgs {
+            sub_steps, ..
+        }) => {
+            state.require_elements(step, path);
+            if !sub_steps
+                .iter()
+                .any(|sub_step| matches!(sub_step, WorkflowStep::ElementPop(..)))
+            {
+                state.violation(
+                    path,
+                    "`ElementPop` is mandatory sub-step of `ElementsLoopThrough`".to_string(),
+                );
+            }
+            state.validate_loop(sub_steps, path);
+            // The loop removes the exhausted collection from the stack
+            state.stack_size = state.stack_size.saturating_sub(1);
+        }
+        WorkflowStep::While(step_args::WhileArgs {
+            condition,
+            sub_steps,
+            ..
+        }) => {
+            for (pattern, error) in condition.invalid_patterns() {
+                state.violation(path, format!("Invalid pattern {pattern:?}: {error}"));
+            }
+            let stack_size = state.stack_size;
+            state.validate_loop(sub_steps, path);
+            state.stack_size = stack_size;
+        }
+        WorkflowStep::PageLoop(step_args::PageLoopArgs { sub_steps, .. })
+        | WorkflowStep::Repeat(step_args::RepeatArgs { sub_steps, .. }) => {
+            // The loop may end at any of its sub-steps, so it is only
+            // known that the stack is left as it was found
+            let stack_size = state.stack_size;
+            state.validate_loop(sub_steps, path);
+            state.stack_size = stack_size;
+        }
+        WorkflowStep::If(step_args::IfArgs {
+            condition,
+            then,
+            else_steps,
+            ..
+        }) => {
+            for (pattern, error) in condition.invalid_patterns() {
+                state.violation(path, format!("Invalid pattern {pattern:?}: {error}"));
+            }
+            // Either branch may run, so both must leave the stack alike
+            let stack_size = state.stack_size;
+            validate_steps(then, &path.child(0), state);
+            let then_stack_size = std::mem::replace(&mut state.stack_size, stack_size);
+            validate_steps(else_steps, &path.child(1), state);
+            if state.stack_size != then_stack_size {
+                state.violation(
+                    path,
+                    "`then` and `else` of `If` must leave the same number of located element collections"
+                        .to_string(),
+                );
+            }
+        }
+        WorkflowStep::Try(step_args::TryArgs {
+            steps,
+            catch,
+            finally,
+            rethrow,
+            ..
+        }) => {
+            let stack_size = state.stack_size;
+            validate_steps(steps, &path.child(0), state);
+            let steps_stack_size = std::mem::replace(&mut state.stack_size, stack_size);
+            // Going on after `catch` must leave the stack as after `steps`
+            validate_steps(catch, &path.child(1), state);
+            if !catch.is_empty() && !rethrow && state.stack_size != steps_stack_size {
+                state.violation(
+                    path,
+                    "`steps` and `catch` of `Try` must leave the same number of located element collections"
+                        .to_string(),
+                );
+            }
+            // `finally` may run right after the first of the steps failed
+            state.stack_size = stack_size;
+            validate_steps(finally, &path.child(2), state);
+            state.stack_size = (steps_stack_size + state.stack_size).saturating_sub(stack_size);
+        }
+        WorkflowStep::BreakIfMissing(..) => {
+            if state.loop_depth == 0 {
+                state.violation(path, format!("`{step}` must be within a loop"));
+            }
+        }
+        WorkflowStep::ElementPop(..)
+        | WorkflowStep::ElementSaveHtmlValue(..)
+        | WorkflowStep::ElementSaveText(..)
+        | WorkflowStep::ElementSaveAttribute(..)
+        | WorkflowStep::ElementSaveProperty(..)
+        | WorkflowStep::ElementSaveCssValue(..)
+        | WorkflowStep::ElementClick(..)
+        | WorkflowStep::ElementSendKeys(..)
+        | WorkflowStep::ElementTakeScreenshot(..) => state.require_elements(step, path),
+        WorkflowStep::ElementClickNewWindow(..) => {
+            state.require_elements(step, path);
+            state.open_windows.push(path.clone());
+        }
+        WorkflowStep::PageBackWindow(..) => {
+            if state.open_windows.pop().is_none() {
+                state.violation(
+                    path,
+                    "`PageBackWindow` requires a preceding `ElementClickNewWindow`".to_string(),
+                );
+            }
+        }
+        WorkflowStep::PageScroll(step_args::PageScrollArgs {
+            mode, page_size, ..
+        }) => match mode {
+            step_args::ScrollMode::ToElement => state.require_elements(step, path),
+            step_args::ScrollMode::Full if page_size.abs() != 1.0 => state.violation(
+                path,
+                format!(
+                    "Incorrect arguments mode={mode:?} and page_size={page_size}, expected \
+                         mode \"full\" with page_size 1.0 or -1.0"
+                ),
+            ),
+            _ => {}
+        },
+        WorkflowStep::WaitForUrl(step_args::WaitForUrlArgs { pattern, .. }) => {
+            if let Err(error) = regex::Regex::new(pattern) {
+                state.violation(path, format!("Invalid pattern: {error}"));
+            }
+        }
+        WorkflowStep::AssertText(step_args::AssertTextArgs {
+            expected, locator, ..
+        })
+        | WorkflowStep::AssertAttribute(step_args::AssertAttributeArgs {
+            expected, locator, ..
+        }) => {
+            if locator.is_none() {
+                state.require_elements(step, path);
+            }
+            check_pattern(expected, path, state);
+        }
+        WorkflowStep::AssertVisible(step_args::AssertVisibleArgs { locator: None, .. }) => {
+            state.require_elements(step, path)
+        }
+        WorkflowStep::AssertUrl(step_args::AssertPageArgs { expected, .. })
+        | WorkflowStep::AssertTitle(step_args::AssertPageArgs { expected, .. })
+        | WorkflowStep::AssertValue(step_args::AssertValueArgs { expected, .. }) => {
+            check_pattern(expected, path, state)
+        }
+        WorkflowStep::PageOpen(..)
+        | WorkflowStep::PageRefresh(..)
+        | WorkflowStep::PageBack(..)
+        | WorkflowStep::PageTakeScreenshot(..)
+        | WorkflowStep::PageWait(..)
+        | WorkflowStep::PageSaveUrl(..)
+        | WorkflowStep::PageSaveTitle(..)
+        | WorkflowStep::WaitForElement(..)
+        | WorkflowStep::WaitForText(..)
+        | WorkflowStep::WaitForJs(..)
+        | WorkflowStep::AssertElementCount(..)
+        | WorkflowStep::AssertVisible(..)
+        | WorkflowStep::PrintCurrentValues(..)
+        | WorkflowStep::ExportValues(..) => {}
+    }
+}
+
+/// Return the transforms of given (save) step.
+fn transforms_of(step: &WorkflowStep) -> &[Transform] {
+    match step {
+        WorkflowStep::ElementSaveHtmlValue(step_args::ElementSaveHtmlValueArgs {
+            transforms,
+            ..
+        })
+        | WorkflowStep::ElementSaveText(step_args::SaveValueArgs { transforms, .. })
+        | WorkflowStep::ElementSaveAttribute(step_args::ElementSaveAttributeArgs {
+            transforms,
+            ..
+        })
+        | WorkflowStep::ElementSaveProperty(step_args::ElementSavePropertyArgs {
+            transforms,
+            ..
+        })
+        | WorkflowStep::ElementSaveCssValue(step_args::ElementSaveCssValueArgs {
+            transforms,
+            ..
+        })
+        | WorkflowStep::PageSaveUrl(step_args::SaveValueArgs { transforms, .. })
+        | WorkflowStep::PageSaveTitle(step_args::SaveValueArgs { transforms, .. }) => transforms,
+        _ => &[],
+    }
+}
+
+/// Return the time between the checks of given (wait) step.
+fn interval_ms_of(step: &WorkflowStep) -> Option<u64> {
+    match step {
+        WorkflowStep::WaitForElement(step_args::WaitForElementArgs { interval_ms, .. })
+        | WorkflowStep::WaitForUrl(step_args::WaitForUrlArgs { interval_ms, .. })
+        | WorkflowStep::WaitForText(step_args::WaitForTextArgs { interval_ms, .. })
+        | WorkflowStep::WaitForJs(step_args::WaitForJsArgs { interval_ms, .. }) => {
+            Some(*interval_ms)
+        }
+        _ => None,
+    }
+}
+
+/// Flag the invalid regular expression of given text match, if any.
+fn check_pattern(text_match: &TextMatch, path: &StepPath, state: &mut State) {
+    if let TextMatch::Regex(pattern) = text_match {
+        if let Err(error) = regex::Regex::new(pattern) {
+            state.violation(path, format!("Invalid pattern: {error}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violation_paths(content: &str) -> Vec<String> {
+        let wf: Workflow = serde_yaml::from_str(content).expect("Failed to parse workflow");
+        validate(&wf)
+            .iter()
+            .map(|violation| violation.path().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn validate_sample_workflow() {
+        let content = std::fs::read_to_string("src/sample_workflow.yaml")
+            .expect("Failed to read sample workflow");
+
+        assert_eq!(violation_paths(&content), Vec::<String>::new());
+    }
+
+    #[test]
+    fn validate_element_stack_discipline() {
+        let content = r#"
+          name: "invalid workflow"
+          steps:
+              - !ElementClick [false, false]
+              - !PageLocateElements ["body div", "all", 0]
+              - !ElementsLoopThrough
+                - !ElementSendKeys "keys"
+              - !ElementPop
+        "#;
+
+        assert_eq!(violation_paths(content), vec!["step 0", "step 2", "step 3"]);
+    }
+
+    #[test]
+    fn validate_element_pop_anywhere_in_loop() {
+        let content = r#"
+          name: "pop first"
+          steps:
+              - !PageLocateElements "div.card"
+              - !ElementsLoopThrough
+                - !ElementSaveHtmlValue "card"
+                - !ElementPop
+                - !PageWait 100
+              - !PageLocateElements "div.card"
+              - !ElementsLoopThrough
+                - !ElementSaveHtmlValue "card"
+        "#;
+
+        assert_eq!(violation_paths(content), vec!["step 3"]);
+    }
+
+    #[test]
+    fn validate_nested_loops_over_children() {
+        let content = r#"
+          name: "nested loops"
+          steps:
+              - !PageLocateElements "div.card"
+              - !ElementsLoopThrough
+                - !ElementLocateChildren "span.price"
+                - !ElementsLoopThrough
+                  - !ElementSaveHtmlValue "price"
+                  - !ElementPop
+                - !ElementPop
+              - !ElementLocateChildren "span.price"
+        "#;
+
+        assert_eq!(violation_paths(content), vec!["step 2"]);
+    }
+
+    #[test]
+    fn validate_window_pairing_and_modes() {
+        let content = r#"
+          name: "invalid workflow"
+          steps:
+              - !PageBackWindow
+              - !PageLocateElements ["a", !range [2, 2]]
+              - !ElementsLoopThrough
+                - !ElementClickNewWindow
+                - !ElementPop
+              - !PageScroll ["to-element"]
+              - !PageScroll ["full", 0.5]
+              - !PageScroll ["full", -1.0]
+              - !PageScroll {page_size: 0.5}
+        "#;
+
+        assert_eq!(
+            violation_paths(content),
+            vec!["step 0", "step 1", "step 2.0", "step 3", "step 4"]
+        );
+    }
+
+    #[test]
+    fn validate_wait_patterns() {
+        let content = r#"
+          name: "waits"
+          steps:
+              - !WaitForElement ["div.results", "visible"]
+              - !WaitForUrl {pattern: "/search\\?q=", timeout_ms: 5000}
+              - !WaitForUrl "(unclosed"
+              - !WaitForJs "window.appReady"
+              - !WaitForText {text: "Results", interval_ms: 0}
+        "#;
+
+        assert_eq!(violation_paths(content), vec!["step 2", "step 4"]);
+    }
+
+    #[test]
+    fn validate_assertions() {
+        let content = r#"
+          name: "assertions"
+          steps:
+              - !AssertTitle [!contains "Wikipedia"]
+              - !AssertText "Welcome"
+              - !AssertUrl {expected: !regex "(unclosed"}
+              - !PageLocateElements "h1"
+              - !ElementsLoopThrough
+                - !AssertText [!regex "^Wiki", "h1 strong"]
+                - !AssertVisible
+                - !ElementPop
+              - !AssertElementCount ["div.card", 3]
+        "#;
+
+        assert_eq!(violation_paths(content), vec!["step 1", "step 2"]);
+    }
+
+    #[test]
+    fn validate_transform_patterns() {
+        let content = r#"
+          name: "transforms"
+          steps:
+              - !PageSaveTitle {name: "title", transforms: [trim, !capture '(\w+)']}
+              - !PageSaveUrl {name: "id", transforms: [!replace ["(unclosed", ""]]}
+        "#;
+
+        assert_eq!(violation_paths(content), vec!["step 1"]);
+    }
+
+    #[test]
+    fn validate_if_branches() {
+        let content = r##"
+          name: "branches"
+          steps:
+              - !If
+                condition: !url_matches "(unclosed"
+                then:
+                  - !PageLocateElements "button.accept"
+                  - !ElementsLoopThrough
+                    - !ElementClick
+                    - !ElementPop
+              - !If
+                condition: "#cookie-banner"
+                then:
+                  - !PageLocateElements "button.accept"
+                else:
+                  - !ElementClick
+        "##;
+
+        assert_eq!(
+            violation_paths(content),
+            vec!["step 0", "step 1.1.0", "step 1"]
+        );
+    }
+
+    #[test]
+    fn validate_break_within_loops() {
+        let content = r#"
+          name: "breaks"
+          steps:
+              - !BreakIfMissing "a.next"
+              - !PageLoop
+                - !BreakIfMissing "a.next"
+                - !If
+                  condition: !js "window.done"
+                  then:
+                    - !BreakIfMissing ".more"
+              - !If
+                condition: ".more"
+                then:
+                  - !BreakIfMissing ".more"
+        "#;
+
+        assert_eq!(violation_paths(content), vec!["step 0", "step 2.0.0"]);
+    }
+
+    #[test]
+    fn validate_on_error_policies() {
+        let content = r#"
+          name: "policies"
+          steps:
+              - !PageWait {duration_ms: 100, on_error: skip_iteration}
+              - !ElementClick {on_error: continue}
+              - !PageLocateElements "div.product"
+              - !ElementsLoopThrough
+                - !ElementSaveText {name: "price", on_error: skip_iteration}
+                - !ElementPop
+        "#;
+
+        assert_eq!(violation_paths(content), vec!["step 0", "step 1"]);
+    }
+
+    #[test]
+    fn validate_try_blocks() {
+        let content = r#"
+          name: "recovery"
+          steps:
+              - !Try
+                steps:
+                  - !PageLocateElements "form.login"
+                  - !ElementsLoopThrough
+                    - !ElementClick
+                    - !ElementPop
+                catch:
+                  - !PageTakeScreenshot "login_failed"
+                finally:
+                  - !ElementClick
+              - !Try
+                steps:
+                  - !PageLocateElements "div.product"
+                catch:
+                  - !PageTakeScreenshot "no_products"
+                rethrow: true
+              - !ElementClick
+        "#;
+
+        assert_eq!(violation_paths(content), vec!["step 0.2.0"]);
+    }
+}
@@ -0,0 +1,281 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// `Record` is a set of named values, such as the values saved within an
+/// iteration of a loop.
+pub type Record = Map<String, Value>;
+
+/// `ValueStore` struct holds the values saved by the workflow steps (such
+/// as `ElementSaveHtmlValue`), by name.
+///
+/// The values are saved to the current record, which is the root record of
+/// the workflow, unless a loop with `record` started a new record for its
+/// iteration. At the end of the iteration, the record is appended to the
+/// list (of the given name) within the enclosing record, so that nested
+/// loops result in nested records. Saving a value with the name of an
+/// already saved one turns it into a list of values, which is told apart
+/// from a saved value that is a list itself, so that the latter is looked
+/// up as a whole.
+///
+/// A scoped value (such as the `error` caught by `Try`) is only there for
+/// the steps running meanwhile, and takes precedence over the saved ones,
+/// without being saved itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueStore {
+    /// Root record, followed by the records of the running loop iterations.
+    records: Vec<Record>,
+    /// Names of the lists of repeated saves, for each of the records.
+    repeated: Vec<BTreeSet<String>>,
+    /// Scoped values, innermost last.
+    scoped: Vec<(String, Value)>,
+}
+
+impl ValueStore {
+    /// Construct an empty value store.
+    pub fn new() -> ValueStore {
+        ValueStore {
+            records: vec![Record::new()],
+            repeated: vec![BTreeSet::new()],
+            scoped: Vec::new(),
+        }
+    }
+
+    /// Save given value with given name to the current record.
+    pub fn save(&mut self, name: &str, value: Value) {
+        self.append(name, value, false);
+    }
+
+    /// Append given value to the list of the values saved with given name
+    /// to the current record, turning the value saved so far (if any) into
+    /// such a list, or else save it (as a list if `as_list` is `true`).
+    fn append(&mut self, name: &str, value: Value, as_list: bool) {
+        let depth = self.records.len() - 1;
+        let is_repeated = self.repeated[depth].contains(name);
+        let record = self.current_mut();
+        match record.get_mut(name) {
+            Some(Value::Array(values)) if is_repeated => values.push(value),
+            Some(previous) => *previous = Value::Array(vec![previous.take(), value]),
+            None if as_list => {
+                record.insert(name.to_string(), Value::Array(vec![value]));
+            }
+            None => {
+                record.insert(name.to_string(), value);
+                return;
+            }
+        }
+        self.repeated[depth].insert(name.to_string());
+    }
+
+    /// Return the innermost scoped value with given name, or else the value
+    /// last saved with that name, looked up from the current record outwards.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        if let Some(value) = self.get_scoped(name) {
+            return Some(value);
+        }
+        let (value, repeated) = self
+            .records
+            .iter()
+            .zip(&self.repeated)
+            .rev()
+            .find_map(|(record, repeated)| Some((record.get(name)?, repeated)))?;
+        match value {
+            Value::Array(values) if repeated.contains(name) => values.last(),
+            value => Some(value),
+        }
+    }
+
+    /// Return the value last saved with given name as text, where strings
+    /// are taken as they are, and other values as JSON.
+    pub fn get_text(&self, name: &str) -> Option<String> {
+        self.get(name).map(to_text)
+    }
+
+    /// Return the innermost scoped value with given name.
+    pub fn get_scoped(&self, name: &str) -> Option<&Value> {
+        self.scoped
+            .iter()
+            .rev()
+            .find_map(|(scoped_name, value)| (scoped_name == name).then_some(value))
+    }
+
+    /// Start the scope of given value, until the matching `end_scope`.
+    pub fn begin_scope(&mut self, name: &str, value: Value) {
+        self.scoped.push((name.to_string(), value));
+    }
+
+    /// End the innermost scope of a value.
+    pub fn end_scope(&mut self) {
+        self.scoped.pop();
+    }
+
+    /// Start a new (current) record, such as for an iteration of a loop.
+    pub fn begin_record(&mut self) {
+        self.records.push(Record::new());
+        self.repeated.push(BTreeSet::new());
+    }
+
+    /// End the current record, and append it to the list with given name
+    /// within the enclosing record. An empty record is dropped.
+    pub fn end_record(&mut self, list_name: &str) {
+        if self.records.len() < 2 {
+            return;
+        }
+        let record = self.records.pop().unwrap_or_default();
+        self.repeated.pop();
+        if record.is_empty() {
+            return;
+        }
+        self.append(list_name, Value::Object(record), true);
+    }
+
+    /// Return the current record.
+    pub fn current(&self) -> &Record {
+        self.records
+            .last()
+            .expect("the root record is never removed")
+    }
+
+    fn current_mut(&mut self) -> &mut Record {
+        self.records
+            .last_mut()
+            .expect("the root record is never removed")
+    }
+
+    /// Return the root record, with the values saved so far.
+    pub fn root(&self) -> &Record {
+        &self.records[0]
+    }
+
+    /// Return the root record, dropping the records still running (if any).
+    pub fn into_root(mut self) -> Record {
+        self.records.truncate(1);
+        self.repeated.truncate(1);
+        self.records.pop().unwrap_or_default()
+    }
+}
+
+impl Default for ValueStore {
+    fn default() -> Self {
+        ValueStore::new()
+    }
+}
+
+/// Return given value as text, where strings are taken as they are, and
+/// other values as JSON.
+pub fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn repeated_saves_become_list() {
+        let mut store = ValueStore::new();
+
+        store.save("title", json!("Wikipedia"));
+        store.save("heading", json!("English"));
+        store.save("heading", json!("Deutsch"));
+
+        assert_eq!(
+            Value::Object(store.into_root()),
+            json!({"title": "Wikipedia", "heading": ["English", "Deutsch"]})
+        );
+    }
+
+    #[test]
+    fn nested_records_per_iteration() {
+        let mut store = ValueStore::new();
+        store.save("page", json!(1));
+
+        for (name, prices) in [("Pen", vec![2, 3]), ("Ink", vec![5])] {
+            store.begin_record();
+            store.save("name", json!(name));
+            for price in prices {
+                store.begin_record();
+                store.save("price", json!(price));
+                store.end_record("offers");
+            }
+            // an empty record is dropped
+            store.begin_record();
+            store.end_record("offers");
+            store.end_record("products");
+        }
+
+        assert_eq!(
+            Value::Object(store.into_root()),
+            json!({
+                "page": 1,
+                "products": [
+                    {"name": "Pen", "offers": [{"price": 2}, {"price": 3}]},
+                    {"name": "Ink", "offers": [{"price": 5}]},
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn get_looks_up_current_record_outwards() {
+        let mut store = ValueStore::new();
+        store.save("term", json!("rust"));
+        store.save("link", json!("https://old.example.com"));
+        store.begin_record();
+        store.save("link", json!("https://new.example.com"));
+        store.save("count", json!(3));
+
+        assert_eq!(
+            store.get_text("link").as_deref(),
+            Some("https://new.example.com")
+        );
+        assert_eq!(store.get_text("term").as_deref(), Some("rust"));
+        assert_eq!(store.get_text("count").as_deref(), Some("3"));
+        assert_eq!(store.get("missing"), None);
+
+        store.end_record("items");
+        assert_eq!(
+            store.get_text("link").as_deref(),
+            Some("https://old.example.com")
+        );
+    }
+
+    #[test]
+    fn saved_list_is_looked_up_whole() {
+        let mut store = ValueStore::new();
+
+        store.save("tags", json!(["a", "b"]));
+        assert_eq!(store.get("tags"), Some(&json!(["a", "b"])));
+        assert_eq!(store.get_text("tags").as_deref(), Some("[\"a\",\"b\"]"));
+
+        store.save("tags", json!("c"));
+        store.save("tags", json!(["d"]));
+        assert_eq!(store.get("tags"), Some(&json!(["d"])));
+        assert_eq!(
+            Value::Object(store.into_root()),
+            json!({"tags": [["a", "b"], "c", ["d"]]})
+        );
+    }
+
+    #[test]
+    fn scoped_value_takes_precedence() {
+        let mut store = ValueStore::new();
+        store.save("error", json!("saved"));
+
+        store.begin_scope("error", json!("caught"));
+        store.begin_record();
+        store.save("status", json!("recovered"));
+        assert_eq!(store.get_text("error").as_deref(), Some("caught"));
+        store.end_record("attempts");
+        store.end_scope();
+
+        assert_eq!(store.get_text("error").as_deref(), Some("saved"));
+        assert_eq!(
+            Value::Object(store.into_root()),
+            json!({"error": "saved", "attempts": [{"status": "recovered"}]})
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use crate::error::{SquirrelError, StepPath};
+use crate::wf::step_args::ElementState;
+use std::future::Future;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, Instant};
+
+/// `AUTO_WAIT_INTERVAL_MS` defines the time between the checks of the
+/// element lookups waiting for the elements to appear.
+pub const AUTO_WAIT_INTERVAL_MS: u64 = 250;
+
+/// `DOM_STABLE_TIMEOUT_MS` defines the time to wait for the DOM to be stable
+/// when the element lookup itself doesn't wait (its `timeout_ms` is 0), as
+/// it takes at least two checks to tell.
+pub const DOM_STABLE_TIMEOUT_MS: u64 = 2000;
+
+/// Run given check every `interval_ms` until it returns `true`, and return
+/// a timeout error (mentioning given condition) if it doesn't do so within
+/// `timeout_ms`, where the last check is made right when the time is up.
+/// An error returned by the check is returned right away.
+pub async fn poll_until<F, Fut>(
+    timeout_ms: u64,
+    interval_ms: u64,
+    condition: &str,
+    mut check: F,
+) -> Result<(), SquirrelError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<bool, SquirrelError>>,
+{
+    let started_at = Instant::now();
+    let timeout = Duration::from_millis(timeout_ms);
+    loop {
+        if check().await? {
+            return Ok(());
+        }
+        if started_at.elapsed() >= timeout {
+            return Err(SquirrelError::Timeout {
+                path: StepPath::default(),
+                message: format!("Waited {timeout_ms}ms for {condition}"),
+            });
+        }
+        sleep_within(started_at, timeout, interval_ms).await;
+    }
+}
+
+/// Run given attempt every `interval_ms` until its outcome is accepted by
+/// `is_done`, or `timeout_ms` has passed, and return the last outcome.
+pub async fn retry_until<T, F, Fut>(
+    timeout_ms: u64,
+    interval_ms: u64,
+    mut attempt: F,
+    is_done: impl Fn(&T) -> bool,
+) -> T
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = T>,
+{
+    let started_at = Instant::now();
+    let timeout = Duration::from_millis(timeout_ms);
+    loop {
+        let outcome = attempt().await;
+        if is_done(&outcome) || started_at.elapsed() >= timeout {
+            return outcome;
+        }
+        sleep_within(started_at, timeout, interval_ms).await;
+    }
+}
+
+/// Sleep for `interval_ms`, but no longer than the time left until given
+/// timeout (counted from `started_at`).
+async fn sleep_within(started_at: Instant, timeout: Duration, interval_ms: u64) {
+    let left = timeout.saturating_sub(started_at.elapsed());
+    tokio::time::sleep(Duration::from_millis(interval_ms).min(left)).await;
+}
+
+/// Wait (up to `timeout_ms`, or `DOM_STABLE_TIMEOUT_MS` if it is 0) until the
+/// current page is loaded, and the number of its elements stays the same
+/// between two consecutive checks.
+pub async fn dom_stable(
+    conn_webdriver: &fantoccini::Client,
+    timeout_ms: u64,
+    interval_ms: u64,
+) -> Result<(), SquirrelError> {
+    let timeout_ms = dom_stable_timeout_ms(timeout_ms);
+    let script = "return document.readyState === 'complete' \
+        ? document.getElementsByTagName('*').length : -1;";
+    let last_count = AtomicI64::new(-1);
+    poll_until(timeout_ms, interval_ms, "DOM to be stable", || async {
+        let count = conn_webdriver
+            .execute(script, Vec::new())
+            .await?
+            .as_i64()
+            .unwrap_or(-1);
+        Ok(count >= 0 && last_count.swap(count, Ordering::Relaxed) == count)
+    })
+    .await
+}
+
+/// Return the time to wait for the DOM to be stable, given the timeout of
+/// the element lookup.
+fn dom_stable_timeout_ms(timeout_ms: u64) -> u64 {
+    if timeout_ms == 0 {
+        DOM_STABLE_TIMEOUT_MS
+    } else {
+        timeout_ms
+    }
+}
+
+/// Return `true` if given elements (found by a locator) are in given state.
+pub async fn is_in_state(
+    elements: &[fantoccini::elements::Element],
+    state: &ElementState,
+) -> Result<bool, fantoccini::error::CmdError> {
+    match state {
+        ElementState::Present => Ok(!elements.is_empty()),
+        ElementState::Visible => any_displayed(elements, false).await,
+        ElementState::Clickable => any_displayed(elements, true).await,
+        ElementState::Gone => Ok(!any_displayed(elements, false).await?),
+    }
+}
+
+/// Return `true` if any of given elements is displayed (and also enabled,
+/// if `clickable` is `true`).
+async fn any_displayed(
+    elements: &[fantoccini::elements::Element],
+    clickable: bool,
+) -> Result<bool, fantoccini::error::CmdError> {
+    for elem in elements {
+        let result = match elem.is_displayed().await {
+            Ok(true) if clickable => elem.is_enabled().await,
+            other => other,
+        };
+        match result {
+            Ok(true) => return Ok(true),
+            Ok(false) => {}
+            // the element got removed from the page meanwhile
+            Err(error) if is_stale(&error) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(false)
+}
+
+/// Return `true` if the error is due to an element no longer on the page.
+pub fn is_stale(error: &fantoccini::error::CmdError) -> bool {
+    match error {
+        fantoccini::error::CmdError::Standard(error) => error.error() == "stale element reference",
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn poll_until_condition_is_met() {
+        let mut checks = 0;
+
+        let result = poll_until(1000, 1, "third check", || {
+            checks += 1;
+            let checks = checks;
+            async move { Ok(checks == 3) }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(checks, 3);
+    }
+
+    #[tokio::test]
+    async fn poll_until_times_out_with_condition() {
+        let result = poll_until(20, 5, "element css=div to be visible", || async {
+            Ok(false)
+        })
+        .await;
+
+        match result {
+            Err(SquirrelError::Timeout { message, .. }) => {
+                assert_eq!(message, "Waited 20ms for element css=div to be visible")
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn poll_until_keeps_within_timeout() {
+        let started_at = Instant::now();
+        let mut checks = 0;
+
+        let result = poll_until(30, 10000, "anything", || {
+            checks += 1;
+            async { Ok(false) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(SquirrelError::Timeout { .. })));
+        assert!(started_at.elapsed() < Duration::from_millis(1000));
+        assert_eq!(checks, 2);
+    }
+
+    #[test]
+    fn dom_stable_waits_without_timeout() {
+        assert_eq!(dom_stable_timeout_ms(0), DOM_STABLE_TIMEOUT_MS);
+        assert_eq!(dom_stable_timeout_ms(5000), 5000);
+    }
+
+    #[tokio::test]
+    async fn poll_until_returns_check_error() {
+        let result = poll_until(1000, 1, "anything", || async {
+            Err(SquirrelError::Internal {
+                path: StepPath::default(),
+                message: String::from("broken check"),
+            })
+        })
+        .await;
+
+        assert!(matches!(result, Err(SquirrelError::Internal { .. })));
+    }
+
+    #[tokio::test]
+    async fn retry_until_returns_last_outcome() {
+        let mut attempts = 0;
+
+        let outcome = retry_until(
+            1000,
+            1,
+            || {
+                attempts += 1;
+                let attempts = attempts;
+                async move { attempts }
+            },
+            |attempts| *attempts == 2,
+        )
+        .await;
+        assert_eq!(outcome, 2);
+
+        let outcome = retry_until(0, 1, || async { "not found" }, |_| false).await;
+        assert_eq!(outcome, "not found");
+    }
+}
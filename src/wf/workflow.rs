@@ -1,8 +1,39 @@
+use crate::wf::error_policy::{self, ErrorPolicy};
 use crate::wf::workflow_step;
+use std::collections::BTreeMap;
 
 /// `Workflow` struct defines workflow name and sequence of steps to be executed.
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug)]
 pub struct Workflow {
+    /// Name of the workflow (also used to name the page screenshots).
     pub name: String,
+    /// Variables the string arguments of the steps can refer to as `${name}`
+    /// (overridden by the `vars` of `Config`, such as given by `--var`).
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+    /// Time the element lookups (such as `PageLocateElements`) keep polling
+    /// for the elements to appear, unless given by the step itself (default
+    /// 0, i.e. look up once).
+    #[serde(default)]
+    pub timeout_ms: u64,
+    /// Whether the element lookups first wait (up to `timeout_ms`, or 2s if
+    /// it is 0) for the page to be loaded and its DOM to stop changing,
+    /// unless given by the step itself (default false).
+    #[serde(default)]
+    pub wait_dom_stable: bool,
+    /// Whether the failed assertions (such as `AssertText`) are collected and
+    /// reported at the end of the run, rather than stopping the run at the
+    /// first one (default false).
+    #[serde(default)]
+    pub soft_assertions: bool,
+    /// What to do when a step fails, unless given by the step itself (see
+    /// `ErrorPolicy`): one of "fail" (default), "continue" and "skip_iteration",
+    /// or `!retry {times: 3, backoff_ms: 500}`. The loops, `If` and `Try` fail
+    /// only as their sub-steps do, and so they follow "fail" unless given
+    /// their own, as do the errors an enclosing loop breaks on (see `break_on`).
+    #[serde(default)]
+    #[schemars(schema_with = "error_policy::error_policy_schema")]
+    pub on_error: ErrorPolicy,
+    /// Steps to be executed, in order.
     pub steps: Vec<workflow_step::WorkflowStep>,
 }
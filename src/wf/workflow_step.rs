@@ -1,22 +1,81 @@
+use crate::error::{SquirrelError, StepPath};
+use crate::wf::condition::Condition;
+use crate::wf::error_policy::ErrorPolicy;
+use crate::wf::locator::Locator;
+use crate::wf::loops::{BreakOn, LoopEnd, LoopGuard};
+use crate::wf::transform::{self, Transform};
+use crate::wf::value_store::ValueStore;
+use crate::wf::{assertion, export, step_args, template, wait, workflow};
 use crate::{config, utils};
+use strum::{EnumMessage, IntoEnumIterator};
 
 /// `WorkflowStep` enum defines individual step of `Workflow`.
 /// Its variants define supported operations.
-#[derive(serde::Serialize, serde::Deserialize, Debug, strum_macros::Display)]
+///
+/// A step can be given either as a YAML tag (such as `!PageWait 100`), or
+/// as a single-entry map (such as `PageWait: 100`). Its arguments can be
+/// given either positionally (such as `!ElementClick [true, false]`), or by
+/// name (such as `!ElementClick {check_enabled: true}`), see `step_args`.
+///
+/// Every step also takes `on_error` by name, the policy for when the step
+/// fails (such as `!ElementSaveText {name: "price", on_error:
+/// skip_iteration}`, or `!PageRefresh {on_error: !retry {times: 2}}` for a
+/// step without other arguments), see `ErrorPolicy`.
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+    Debug,
+    strum_macros::Display,
+    strum_macros::EnumIter,
+    strum_macros::EnumMessage,
+)]
+#[serde(remote = "Self")]
 pub enum WorkflowStep {
     /// Find elements on current page.
     ///
-    /// Use given css as selector.
+    /// Use given locator (a CSS selector by default, or else one of `xpath`,
+    /// `link_text`, `partial_link_text`, `id` and `text_contains`, such as
+    /// `{xpath: "//div"}`) to find the elements.
     /// If running in "all" mode, search all elements, ignoring the index.
     /// If running in "index" mode, then only select the element at given index.
-    /// If no matching element is found, return an error.
+    /// If running in "first" or "last" mode, only select the first or last element.
+    /// If running in "range" mode (such as `!range [0, 3]`), select the elements
+    /// from start up to (excluding) end.
+    /// If running in "filter" mode (such as `!filter "English"`), select the
+    /// elements whose visible text contains given text.
+    /// If no matching element is found in "index", "first", "last" or "range"
+    /// mode, return an error.
+    /// With a (step or workflow) `timeout_ms`, keep looking up until matching
+    /// elements appear, and with `wait_dom_stable`, first wait for the page to
+    /// stop changing.
     ///
     /// Logic: Calling this means that there is going to be a beginning of new loop
     /// using `ElementsLoopThrough`. So,
     /// - New `current_elements` will be populated and appended to `current_elements_stack`
     ///
-    /// Arguments: `css` (`String`), `mode` ("all"/"index") (`String`), `index` (`usize`)
-    PageLocateElements(String, String, usize),
+    /// Arguments: `locator` (`Locator`, also named `css`), `mode` (`LocateMode`, default "all"),
+    /// `index` (`usize`, default 0), `timeout_ms` (`Option<u64>`, default of the workflow),
+    /// `wait_dom_stable` (`Option<bool>`, default of the workflow)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::LocateElementsArgs>")]
+    PageLocateElements(step_args::LocateElementsArgs),
+    /// Find elements within the currently selected element (such as the
+    /// price within a product card), in the same way as `PageLocateElements`.
+    ///
+    /// It is associated with `ElementsLoopThrough` for the currently
+    /// selected page element (the top element of current-page-elements Vector).
+    ///
+    /// Logic: The found elements are appended as new `current_elements` to
+    /// `current_elements_stack`, to be looped through by a nested `ElementsLoopThrough`
+    /// (before the `ElementPop` of the outer one).
+    ///
+    /// Arguments: `locator` (`Locator`, also named `css`), `mode` (`LocateMode`, default "all"),
+    /// `index` (`usize`, default 0), `timeout_ms` (`Option<u64>`, default of the workflow),
+    /// `wait_dom_stable` (`Option<bool>`, default of the workflow)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::LocateElementsArgs>")]
+    ElementLocateChildren(step_args::LocateElementsArgs),
     /// Loop through latest set of current_elements (from current_elements_set).
     ///
     /// Logic: Calling this means, we wish to iterate over recently set of `current_elements`
@@ -24,11 +83,20 @@ pub enum WorkflowStep {
     /// Also, remove the collection from top of the stack, once it's empty.
     /// Note: In each iteration from the collection on the top of the stack, the element
     /// should be removed using `ElementPop`.
+    /// With `record` (such as "products"), each iteration saves its values to a
+    /// new record, appended to the list of that name within the enclosing record.
+    /// A sub-step failing with `on_error: skip_iteration` (see `ErrorPolicy`) goes
+    /// on with the next element.
     ///
-    /// Arguments: `sub_steps` (`Vec<WorkflowStep>`)
-    ElementsLoopThrough(Vec<WorkflowStep>),
+    /// Arguments: `sub_steps` (`Vec<WorkflowStep>`), `record` (`Option<String>`, default none)
+    #[serde(deserialize_with = "step_args::deserialize_sub_steps")]
+    #[schemars(schema_with = "step_args::sub_steps_schema::<step_args::ElementsLoopThroughArgs>")]
+    ElementsLoopThrough(step_args::ElementsLoopThroughArgs),
     /// Run an infinite loop, with given sub-steps. Break the loop if any of
     /// its sub-steps returns error (such as "NEXT" button is not more active).
+    /// Only the errors given by `break_on` (an element not found, by default)
+    /// end the loop, or else a `BreakIfMissing` sub-step; any other error
+    /// (such as a lost WebDriver session) fails the step.
     ///
     /// For example, this is helpful in the conditions where you want to
     /// keep clicking on "Next" button in pagination, until all pages are
@@ -38,244 +106,1201 @@ pub enum WorkflowStep {
     /// certain elements to process, and so there is always expected a
     /// PageLocateElement somewhere at the beginning of this loop.
     /// Also, this elements itself doesn't touch any of the saved values
-    /// such as current_elements_stack, current_values.
+    /// such as current_elements_stack, value_store.
     /// These will be handled by `PageLocateElement` as its sub-step.
+    /// With `record` (such as "pages"), each iteration saves its values to a
+    /// new record, in the same way as `ElementsLoopThrough`.
+    /// With `max_iterations` or `max_duration_ms`, the loop also ends once the
+    /// limit is reached (such as when the "Next" button never disappears).
+    /// The reason the loop ended is reported along with its iterations.
+    ///
+    /// Arguments: `sub_steps` (`Vec<WorkflowStep>`), `record` (`Option<String>`, default none),
+    /// `max_iterations` (`Option<usize>`, default unbounded), `max_duration_ms`
+    /// (`Option<u64>`, default unbounded), `break_on` (`Vec<BreakOn>`, default
+    /// `[element_not_found]`)
+    #[serde(deserialize_with = "step_args::deserialize_sub_steps")]
+    #[schemars(schema_with = "step_args::sub_steps_schema::<step_args::PageLoopArgs>")]
+    PageLoop(step_args::PageLoopArgs),
+    /// Execute given sub-steps given number of times, such as to load more
+    /// results by clicking "Show more" three times. Unlike `PageLoop`, an
+    /// error of a sub-step fails the step, unless given by `break_on`.
+    ///
+    /// Arguments: `times` (`usize`), `sub_steps` (`Vec<WorkflowStep>`), `record`
+    /// (`Option<String>`, default none), `break_on` (`Vec<BreakOn>`, default none)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::RepeatArgs>")]
+    Repeat(step_args::RepeatArgs),
+    /// Execute given sub-steps as long as given condition holds (checked
+    /// before each iteration, in the same way as the condition of `If`), such
+    /// as `!While [".load-more", [...]]`. Unlike `PageLoop`, an error of a
+    /// sub-step fails the step, unless given by `break_on`.
+    ///
+    /// Arguments: `condition` (`Condition`), `sub_steps` (`Vec<WorkflowStep>`), `record`
+    /// (`Option<String>`, default none), `max_iterations` (`Option<usize>`, default unbounded),
+    /// `max_duration_ms` (`Option<u64>`, default unbounded), `break_on` (`Vec<BreakOn>`,
+    /// default none)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::WhileArgs>")]
+    While(step_args::WhileArgs),
+    /// End the enclosing loop (such as `PageLoop`) unless any element is found
+    /// by given locator, such as the "Next" link in pagination. The elements
+    /// are looked up right away, without waiting.
+    ///
+    /// Unlike relying on `break_on`, it ends the loop only where intended,
+    /// while the other sub-steps failing still fail the workflow.
+    ///
+    /// Arguments: `locator` (`Locator`)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::BreakIfMissingArgs>")]
+    BreakIfMissing(step_args::BreakIfMissingArgs),
+    /// Execute the `then` sub-steps if given condition holds, or else the
+    /// `else` sub-steps (if any), such as to dismiss a cookie banner only
+    /// when it shows up: `!If {condition: "#cookie-banner", then: [...]}`.
     ///
-    /// Arguments: `sub_steps` (`Vec<WorkflowStep>`)
-    PageLoop(Vec<WorkflowStep>),
+    /// The condition is either a CSS selector of an element expected to
+    /// exist, or one of `element_exists`, `text_matches`, `url_matches`,
+    /// `value_matches`, `js` and `not` (such as `!url_matches "/login$"`),
+    /// checked right away without waiting. The sub-steps of `then` are
+    /// located as `<step>.0.<index>`, and the ones of `else` as `<step>.1.<index>`.
+    ///
+    /// Arguments: `condition` (`Condition`), `then` (`Vec<WorkflowStep>`), `else`
+    /// (`Vec<WorkflowStep>`, default none)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::IfArgs>")]
+    If(step_args::IfArgs),
+    /// Execute the `steps` sub-steps, and if any of them fails, the `catch`
+    /// sub-steps (if any), with the error message as `${error}`, such as to
+    /// take a screenshot of a failed login. The `finally` sub-steps are
+    /// executed in the end, whether a sub-step failed or not, such as to
+    /// log out before the workflow fails.
+    ///
+    /// The error is caught only with `catch` sub-steps, and still fails the
+    /// step afterwards with `rethrow`, while an error of `finally` fails the
+    /// step regardless. The elements located by the failing sub-steps are
+    /// dropped. The sub-steps are located as `<step>.0.<index>` (`steps`),
+    /// `<step>.1.<index>` (`catch`) and `<step>.2.<index>` (`finally`).
+    ///
+    /// Arguments: `steps` (`Vec<WorkflowStep>`), `catch` (`Vec<WorkflowStep>`, default
+    /// none), `finally` (`Vec<WorkflowStep>`, default none), `rethrow` (`bool`, default false)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::TryArgs>")]
+    Try(step_args::TryArgs),
     /// Remove current element from the currently selected page elements.
     ///
     /// It is associated with `ElementsLoopThrough` for the currently
     /// selected page element (the top element of current-page-elements Vector).
     ///
-    /// Note: It is mandatory last sub-step for sub steps under `ElementsLoopThrough`.
-    ElementPop,
+    /// Note: It is mandatory sub-step for sub steps under `ElementsLoopThrough`,
+    /// usually the last one.
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::NoArgs>")]
+    ElementPop(step_args::NoArgs),
     /// Save HTML value of the currently selected element.
     ///
     /// It is associated with `ElementsLoopThrough` for the currently
     /// selected page element (the top element of current-page-elements Vector).
+    /// The value is saved with given name to the current record (see `record`
+    /// of the loops), where saving it again turns it into a list of values.
+    /// Like the other save steps, the value can be cleaned up first by given
+    /// `transforms` (such as `[trim, !capture "(\d+)", parse_number]`).
+    ///
+    /// Arguments: `name` (`String`), `is_inner` (`bool`, default `false`), `transforms` (`Vec<Transform>`,
+    /// default none)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::ElementSaveHtmlValueArgs>")]
+    ElementSaveHtmlValue(step_args::ElementSaveHtmlValueArgs),
+    /// Save the visible text of the currently selected element.
+    ///
+    /// Arguments: `name` (`String`), `transforms` (`Vec<Transform>`, default none)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::SaveValueArgs>")]
+    ElementSaveText(step_args::SaveValueArgs),
+    /// Save the value of given HTML attribute (such as `href`) of the
+    /// currently selected element, or `null` if it has no such attribute.
+    ///
+    /// Arguments: `name` (`String`), `attr` (`String`), `transforms` (`Vec<Transform>`,
+    /// default none)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::ElementSaveAttributeArgs>")]
+    ElementSaveAttribute(step_args::ElementSaveAttributeArgs),
+    /// Save the value of given DOM property (such as `value` of an input,
+    /// which changes as the user types) of the currently selected element,
+    /// or `null` if it has no such property.
+    ///
+    /// Arguments: `name` (`String`), `prop` (`String`), `transforms` (`Vec<Transform>`,
+    /// default none)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::ElementSavePropertyArgs>")]
+    ElementSaveProperty(step_args::ElementSavePropertyArgs),
+    /// Save the computed value of given CSS property (such as `color`) of
+    /// the currently selected element.
+    ///
+    /// Arguments: `name` (`String`), `property` (`String`), `transforms` (`Vec<Transform>`,
+    /// default none)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::ElementSaveCssValueArgs>")]
+    ElementSaveCssValue(step_args::ElementSaveCssValueArgs),
+    /// Save the URL of the current page.
+    ///
+    /// Arguments: `name` (`String`), `transforms` (`Vec<Transform>`, default none)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::SaveValueArgs>")]
+    PageSaveUrl(step_args::SaveValueArgs),
+    /// Save the title of the current page.
     ///
-    /// Arguments: `name` (`String`), `is_inner` (`bool`)
-    ElementSaveHtmlValue(String, bool),
+    /// Arguments: `name` (`String`), `transforms` (`Vec<Transform>`, default none)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::SaveValueArgs>")]
+    PageSaveTitle(step_args::SaveValueArgs),
     /// Open given url.
     ///
     /// Arguments: `url` (`String`)
-    PageOpen(String),
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::PageOpenArgs>")]
+    PageOpen(step_args::PageOpenArgs),
     /// Refresh the current page.
-    PageRefresh,
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::NoArgs>")]
+    PageRefresh(step_args::NoArgs),
     /// Perform Go Back operation within the same window/tab.
-    PageBack,
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::NoArgs>")]
+    PageBack(step_args::NoArgs),
     /// Close the current window and go back to previous window.
     ///
     /// Note: ElementClickNewWindow always needs to be paired with PageBackWindow.
-    PageBackWindow,
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::NoArgs>")]
+    PageBackWindow(step_args::NoArgs),
     /// Scroll current page.
     ///
     /// Refer CSS Selectors: https://www.w3schools.com/cssref/css_selectors.php
     /// If running in "page" mode, provide desired `page_size`.
     /// If running in "full" mode, provide `page_size` as 1.0 for scroll to
     /// the bottom, or `page_size` as -1.0 to scroll to the top of the page.
+    /// If running in "pixels" mode, provide `page_size` in pixels.
+    /// If running in "horizontal" mode, provide `page_size` in pages (to the right).
+    /// If running in "to-element" mode, the currently selected element is
+    /// scrolled into view (and `page_size` is ignored).
     ///
-    /// Arguments: `mode` (`String`), `page_size` (`f64`)
-    PageScroll(String, f64),
+    /// Arguments: `mode` (`ScrollMode`, default "page"), `page_size` (`f64`, default 1.0)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::PageScrollArgs>")]
+    PageScroll(step_args::PageScrollArgs),
     /// Take screenshot of current page.
     ///
     /// Arguments: `file_prefix` (`String`)
-    PageTakeScreenshot(String),
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::TakeScreenshotArgs>")]
+    PageTakeScreenshot(step_args::TakeScreenshotArgs),
     /// Wait for given milliseconds.
     ///
-    /// Arguments: `duration_ms` (`String`)
-    PageWait(u64),
+    /// Arguments: `duration_ms` (`u64`)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::PageWaitArgs>")]
+    PageWait(step_args::PageWaitArgs),
+    /// Wait until the elements found by given locator are in given state:
+    /// "present" (on the page), "visible", "clickable" (visible and enabled),
+    /// or "gone" (none of them visible anymore).
+    ///
+    /// Unlike `PageWait`, the wait ends as soon as the condition is met, and
+    /// it fails with a timeout error if the condition is not met in time.
+    ///
+    /// Arguments: `locator` (`Locator`), `state` (`ElementState`, default "present"),
+    /// `timeout_ms` (`u64`, default 10000), `interval_ms` (`u64`, default 250)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::WaitForElementArgs>")]
+    WaitForElement(step_args::WaitForElementArgs),
+    /// Wait until the URL of the current page matches given regular expression.
+    ///
+    /// Arguments: `pattern` (`String`), `timeout_ms` (`u64`, default 10000),
+    /// `interval_ms` (`u64`, default 250)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::WaitForUrlArgs>")]
+    WaitForUrl(step_args::WaitForUrlArgs),
+    /// Wait until given text appears on the current page (or within any of
+    /// the elements found by given locator).
+    ///
+    /// Arguments: `text` (`String`), `locator` (`Option<Locator>`, default the whole page),
+    /// `timeout_ms` (`u64`, default 10000), `interval_ms` (`u64`, default 250)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::WaitForTextArgs>")]
+    WaitForText(step_args::WaitForTextArgs),
+    /// Wait until given JavaScript expression is truthy, such as `window.appReady`.
+    ///
+    /// Arguments: `expression` (`String`), `timeout_ms` (`u64`, default 10000),
+    /// `interval_ms` (`u64`, default 250)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::WaitForJsArgs>")]
+    WaitForJs(step_args::WaitForJsArgs),
     /// Click the currently selected page element.
     ///
     /// It is associated with `ElementsLoopThrough` for the currently
     /// selected page element (the top element of current-page-elements Vector).
     /// If `check_enabled` is `true`, then it raises error if the element is not enabled.
     /// If `check_url` is `true`, then it raised error if the URL of the current page didn't change.
-    /// Arguments: `check_enabled` (`bool`), `check_url` (`bool`)
-    ElementClick(bool, bool),
+    ///
+    /// Arguments: `check_enabled` (`bool`, default `false`), `check_url` (`bool`, default `false`)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::ElementClickArgs>")]
+    ElementClick(step_args::ElementClickArgs),
     /// Click the currently selected page element (must be `a`) and open it in new browser tab.
     ///
     /// Use URL of currently selected element and open it in new window.
     /// Note: ElementClickNewWindow always needs to be paired with PageBackWindow.
-    ElementClickNewWindow,
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::NoArgs>")]
+    ElementClickNewWindow(step_args::NoArgs),
     /// Send keys to currently selected page element.
     ///
     /// It is associated with `ElementsLoopThrough` for the currently
     /// selected page element (the top element of current-page-elements Vector).
     ///
     /// Arguments: `keys` (`String`)
-    ElementSendKeys(String),
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::ElementSendKeysArgs>")]
+    ElementSendKeys(step_args::ElementSendKeysArgs),
     /// Take screenshot of the current element.
     ///
     /// It is associated with `ElementsLoopThrough` for the currently
     /// selected page element (the top element of current-page-elements Vector).
     ///
     /// Arguments: `file_prefix` (`String`)
-    ElementTakeScreenshot(String),
-    PrintCurrentValues,
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::TakeScreenshotArgs>")]
+    ElementTakeScreenshot(step_args::TakeScreenshotArgs),
+    /// Assert the number of the elements found by given locator.
+    ///
+    /// Like the other assertion steps, it fails the workflow with the
+    /// expected and actual values, unless the workflow sets `soft_assertions`,
+    /// in which case the failure is reported at the end of the run instead.
+    ///
+    /// Arguments: `locator` (`Locator`), `count` (`usize`)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::AssertElementCountArgs>")]
+    AssertElementCount(step_args::AssertElementCountArgs),
+    /// Assert the visible text of an element, as the exact text, or as
+    /// `!contains "text"` or `!regex "pattern"`.
+    ///
+    /// Arguments: `expected` (`TextMatch`), `locator` (`Option<Locator>`, default the
+    /// currently selected element)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::AssertTextArgs>")]
+    AssertText(step_args::AssertTextArgs),
+    /// Assert the value of an attribute (such as `href`) of an element.
+    ///
+    /// Arguments: `name` (`String`), `expected` (`TextMatch`), `locator` (`Option<Locator>`,
+    /// default the currently selected element)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::AssertAttributeArgs>")]
+    AssertAttribute(step_args::AssertAttributeArgs),
+    /// Assert the URL of the current page.
+    ///
+    /// Arguments: `expected` (`TextMatch`)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::AssertPageArgs>")]
+    AssertUrl(step_args::AssertPageArgs),
+    /// Assert the title of the current page.
+    ///
+    /// Arguments: `expected` (`TextMatch`)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::AssertPageArgs>")]
+    AssertTitle(step_args::AssertPageArgs),
+    /// Assert that any of the elements found by given locator (or else the
+    /// currently selected element) is displayed.
+    ///
+    /// Arguments: `locator` (`Option<Locator>`, default the currently selected element)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::AssertVisibleArgs>")]
+    AssertVisible(step_args::AssertVisibleArgs),
+    /// Assert the value last saved with given name (such as by `ElementSaveHtmlValue`).
+    ///
+    /// Arguments: `name` (`String`), `expected` (`TextMatch`)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::AssertValueArgs>")]
+    AssertValue(step_args::AssertValueArgs),
+    /// Print all the values saved so far (and the current record, if any).
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::NoArgs>")]
+    PrintCurrentValues(step_args::NoArgs),
+    /// Export all the values saved so far to a file under `temp_dir`, named
+    /// after the workflow like the screenshots. In JSON, the values are
+    /// written as they are, while in NDJSON and CSV, each of the records (such
+    /// as saved by the loops with `record`) makes a row.
+    ///
+    /// Arguments: `format` (`ExportFormat`, default "json"), `file_prefix` (`String`, default
+    /// none), `records` (`Option<String>`, default the records of all the lists)
+    #[serde(deserialize_with = "step_args::deserialize")]
+    #[schemars(schema_with = "step_args::args_schema::<step_args::ExportValuesArgs>")]
+    ExportValues(step_args::ExportValuesArgs),
+}
+
+impl serde::Serialize for WorkflowStep {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        WorkflowStep::serialize(self, serializer)
+    }
+}
+
+impl<'de> step_args::TaggedEnum<'de> for WorkflowStep {
+    fn deserialize_variant<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        WorkflowStep::deserialize(deserializer)
+    }
+
+    /// Deserialize the step given by its bare name (such as `ElementPop`),
+    /// which takes no arguments.
+    fn from_str<E: serde::de::Error>(value: &str) -> Result<Self, E> {
+        let map = serde::de::value::MapDeserializer::new(std::iter::once((value, ())));
+        WorkflowStep::deserialize(serde::de::value::MapAccessDeserializer::new(map))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for WorkflowStep {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        step_args::deserialize_tagged(deserializer)
+    }
+}
+
+const EMPTY_STACK_MESSAGE: &str =
+    "`current_elements_stack` is empty (locate elements with `PageLocateElements` first)";
+
+/// Return the collection of elements at the top of `current_elements_stack`.
+fn current_elements<'a>(
+    current_elements_stack: &'a [Vec<fantoccini::elements::Element>],
+    path: &StepPath,
+) -> Result<&'a Vec<fantoccini::elements::Element>, SquirrelError> {
+    current_elements_stack
+        .last()
+        .ok_or_else(|| SquirrelError::Validation {
+            path: path.clone(),
+            message: EMPTY_STACK_MESSAGE.to_string(),
+        })
+}
+
+/// Return the currently selected element, the top element of `current_elements`.
+fn current_element<'a>(
+    current_elements: &'a [fantoccini::elements::Element],
+    path: &StepPath,
+) -> Result<&'a fantoccini::elements::Element, SquirrelError> {
+    current_elements
+        .last()
+        .ok_or_else(|| SquirrelError::Validation {
+            path: path.clone(),
+            message: "`current_elements` is empty (is `ElementPop` called too often?)".to_string(),
+        })
+}
+
+/// Select the elements (found by given locator) as per given mode.
+async fn select_elements(
+    mut elements: Vec<fantoccini::elements::Element>,
+    locator: &Locator,
+    mode: &step_args::LocateMode,
+    index: usize,
+    path: &StepPath,
+) -> Result<Vec<fantoccini::elements::Element>, SquirrelError> {
+    let len = elements.len();
+    // Not finding the element(s) must raise error to signal the loop (if
+    // running in loop)
+    let not_found = |selection: String| SquirrelError::ElementNotFound {
+        path: path.clone(),
+        message: format!("No element {selection} (out of {len} elements) matching {locator}"),
+    };
+    let selected = match mode {
+        step_args::LocateMode::All => elements,
+        step_args::LocateMode::Index if index < len => vec![elements.remove(index)],
+        step_args::LocateMode::Index => return Err(not_found(format!("at index {index}"))),
+        step_args::LocateMode::First if len > 0 => vec![elements.remove(0)],
+        step_args::LocateMode::Last if len > 0 => vec![elements.remove(len - 1)],
+        step_args::LocateMode::First | step_args::LocateMode::Last => {
+            return Err(not_found(format!("to select as {mode}")))
+        }
+        step_args::LocateMode::Range(start, end) if *start < (*end).min(len) => {
+            elements.drain(*start..(*end).min(len)).collect()
+        }
+        step_args::LocateMode::Range(start, end) => {
+            return Err(not_found(format!("in range {start}..{end}")))
+        }
+        step_args::LocateMode::Filter(text) => {
+            let mut selected = Vec::new();
+            for elem in elements {
+                if elem.text().await?.contains(text.as_str()) {
+                    selected.push(elem);
+                }
+            }
+            selected
+        }
+    };
+    Ok(selected)
+}
+
+/// Find the elements with given `find`, and select them as per the mode.
+///
+/// Unless the (step or workflow) timeout is 0, the lookup is repeated
+/// until the elements appear, such as while the page is still rendering.
+/// The DOM-stability wait is best effort, as some pages never stop changing.
+async fn locate_elements<F, Fut>(
+    mut find: F,
+    args: &step_args::LocateElementsArgs,
+    wf: &workflow::Workflow,
+    conn_webdriver: &fantoccini::Client,
+    depth: usize,
+    path: &StepPath,
+) -> Result<Vec<fantoccini::elements::Element>, SquirrelError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<
+        Output = Result<Vec<fantoccini::elements::Element>, fantoccini::error::CmdError>,
+    >,
+{
+    let timeout_ms = args.timeout_ms.unwrap_or(wf.timeout_ms);
+    if args.wait_dom_stable.unwrap_or(wf.wait_dom_stable) {
+        match wait::dom_stable(conn_webdriver, timeout_ms, wait::AUTO_WAIT_INTERVAL_MS).await {
+            Err(SquirrelError::Timeout { message, .. }) => println!(
+                "{:>width$}Continuing anyway: {message}",
+                "",
+                width = depth * config::TAB_SIZE
+            ),
+            result => result?,
+        }
+    }
+    let step_args::LocateElementsArgs {
+        locator,
+        mode,
+        index,
+        ..
+    } = args;
+    wait::retry_until(
+        timeout_ms,
+        wait::AUTO_WAIT_INTERVAL_MS,
+        || {
+            let found = find();
+            async move { select_elements(found.await?, locator, mode, *index, path).await }
+        },
+        |result| match result {
+            Ok(elements) => !elements.is_empty(),
+            Err(SquirrelError::ElementNotFound { .. }) => false,
+            // the element got removed from the page meanwhile
+            Err(SquirrelError::Driver { source, .. }) => !wait::is_stale(source),
+            Err(_) => true,
+        },
+    )
+    .await
+}
+
+/// Return the element to be checked by an assertion: the first one found by
+/// given locator, or else the currently selected element.
+async fn target_element(
+    locator: &Option<Locator>,
+    conn_webdriver: &fantoccini::Client,
+    current_elements_stack: &[Vec<fantoccini::elements::Element>],
+    path: &StepPath,
+) -> Result<fantoccini::elements::Element, SquirrelError> {
+    match locator {
+        Some(locator) => locator
+            .find_all(conn_webdriver)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| SquirrelError::ElementNotFound {
+                path: path.clone(),
+                message: format!("No element matching {locator}"),
+            }),
+        None => {
+            let current_elements = current_elements(current_elements_stack, path)?;
+            Ok(current_element(current_elements, path)?.clone())
+        }
+    }
+}
+
+/// Describe the element to be checked by an assertion.
+fn describe_target(locator: &Option<Locator>) -> String {
+    match locator {
+        Some(locator) => format!("element {locator}"),
+        None => String::from("current element"),
+    }
+}
+
+/// Append given elements as new `current_elements` to `current_elements_stack`.
+fn push_elements(
+    elements: Vec<fantoccini::elements::Element>,
+    current_elements_stack: &mut Vec<Vec<fantoccini::elements::Element>>,
+    config: &config::Config,
+    depth: usize,
+) {
+    let current_elements_len = elements.len();
+    current_elements_stack.push(elements);
+    println!(
+        "{:>width$}Current Elements Stack Size: {}, Current Elements Size: {}",
+        "",
+        current_elements_stack.len(),
+        current_elements_len,
+        width = depth * config::TAB_SIZE
+    );
+    if config.debug_mode {
+        println!(
+            "{:>width$}[DEBUG] Current Elements Stack: {:?}",
+            "",
+            current_elements_stack,
+            width = depth * config::TAB_SIZE
+        );
+    }
+}
+
+/// Save given value (cleaned up by given transforms) with given name to `value_store`.
+fn save_value(
+    value_store: &mut ValueStore,
+    name: &str,
+    value: serde_json::Value,
+    transforms: &[Transform],
+    depth: usize,
+    path: &StepPath,
+) -> Result<(), SquirrelError> {
+    let value = transform::apply_all(transforms, value, path)?;
+    println!(
+        "{:>width$}Value: {name}: {value}",
+        "",
+        width = depth * config::TAB_SIZE
+    );
+    value_store.save(name, value);
+    Ok(())
+}
+
+/// Execute the sub-steps of a loop iteration, in order, stopping at the
+/// first failing one.
+#[allow(clippy::too_many_arguments)]
+async fn execute_sub_steps(
+    sub_steps: &[WorkflowStep],
+    config: &config::Config,
+    conn_webdriver: &fantoccini::Client,
+    current_elements_stack: &mut Vec<Vec<fantoccini::elements::Element>>,
+    value_store: &mut ValueStore,
+    soft_failures: &mut Vec<SquirrelError>,
+    breaks: &[BreakOn],
+    depth: usize,
+    wf: &workflow::Workflow,
+    path: &StepPath,
+) -> Result<(), SquirrelError> {
+    for (index_sub_step, sub_step) in sub_steps.iter().enumerate() {
+        println!(
+            "{:>width$}SubStep {index_sub_step}: {} (timestamp={})",
+            "",
+            sub_step.to_string(),
+            utils::timestamp(),
+            width = (depth + 1) * config::TAB_SIZE,
+        );
+        sub_step
+            .execute(
+                config,
+                conn_webdriver,
+                current_elements_stack,
+                value_store,
+                soft_failures,
+                breaks,
+                depth + 1,
+                wf,
+                &path.child(index_sub_step),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Execute the sub-steps of a loop iteration, saving their values to a new
+/// record appended to the list with given name (if any), even if a sub-step
+/// failed.
+#[allow(clippy::too_many_arguments)]
+async fn execute_iteration(
+    sub_steps: &[WorkflowStep],
+    record: &Option<String>,
+    config: &config::Config,
+    conn_webdriver: &fantoccini::Client,
+    current_elements_stack: &mut Vec<Vec<fantoccini::elements::Element>>,
+    value_store: &mut ValueStore,
+    soft_failures: &mut Vec<SquirrelError>,
+    breaks: &[BreakOn],
+    depth: usize,
+    wf: &workflow::Workflow,
+    path: &StepPath,
+) -> Result<(), SquirrelError> {
+    if record.is_some() {
+        value_store.begin_record();
+    }
+    let result = execute_sub_steps(
+        sub_steps,
+        config,
+        conn_webdriver,
+        current_elements_stack,
+        value_store,
+        soft_failures,
+        breaks,
+        depth,
+        wf,
+        path,
+    )
+    .await;
+    if let Some(record) = record {
+        value_store.end_record(record);
+    }
+    result
+}
+
+/// Report the error a sub-step with `on_error: skip_iteration` failed with,
+/// at the end of the run.
+fn skip_iteration(error: SquirrelError, soft_failures: &mut Vec<SquirrelError>, depth: usize) {
+    println!(
+        "{:>width$}Iteration skipped: {error}",
+        "",
+        width = depth * config::TAB_SIZE
+    );
+    soft_failures.push(error);
+}
+
+/// Execute the iterations of a loop until a limit of given guard is
+/// reached, or given condition (if any) doesn't hold anymore, and report
+/// the reason the loop ended.
+///
+/// A break (such as by `BreakIfMissing`), or an error of a sub-step given by
+/// `break_on`, ends the loop (dropping the elements located by the
+/// iteration), a skipped iteration goes on with the next one, while any
+/// other error fails it.
+#[allow(clippy::too_many_arguments)]
+async fn execute_loop(
+    sub_steps: &[WorkflowStep],
+    record: &Option<String>,
+    condition: Option<&Condition>,
+    mut guard: LoopGuard,
+    break_on: &[BreakOn],
+    config: &config::Config,
+    conn_webdriver: &fantoccini::Client,
+    current_elements_stack: &mut Vec<Vec<fantoccini::elements::Element>>,
+    value_store: &mut ValueStore,
+    soft_failures: &mut Vec<SquirrelError>,
+    breaks: &[BreakOn],
+    depth: usize,
+    wf: &workflow::Workflow,
+    path: &StepPath,
+) -> Result<(), SquirrelError> {
+    // The sub-steps end this loop, or else the enclosing ones, on these errors
+    let breaks = [break_on, breaks].concat();
+    let end = loop {
+        if let Some(limit) = guard.next_iteration() {
+            break limit;
+        }
+        if let Some(condition) = condition {
+            // The condition may refer to the values saved by the previous iteration
+            let lookup = |name: &str| template::lookup(name, value_store, &config.vars, &wf.vars);
+            let rendered = template::render_args(condition, lookup, path)?;
+            let condition = rendered.as_ref().unwrap_or(condition);
+            if !condition.check(conn_webdriver, value_store, path).await? {
+                break LoopEnd::ConditionFalse;
+            }
+        }
+        println!(
+            "{:>width$}Loop No. {}",
+            "",
+            guard.iterations() - 1,
+            width = depth * config::TAB_SIZE
+        );
+        let stack_size = current_elements_stack.len();
+        let result = execute_iteration(
+            sub_steps,
+            record,
+            config,
+            conn_webdriver,
+            current_elements_stack,
+            value_store,
+            soft_failures,
+            &breaks,
+            depth,
+            wf,
+            path,
+        )
+        .await;
+        let end = match result {
+            Ok(()) => continue,
+            Err(SquirrelError::Skip { source, .. }) => {
+                skip_iteration(*source, soft_failures, depth);
+                current_elements_stack.truncate(stack_size);
+                continue;
+            }
+            Err(SquirrelError::Break { message, .. }) => LoopEnd::Break(message),
+            Err(error) if break_on.iter().any(|kind| kind.matches(&error)) => {
+                LoopEnd::Error(error.to_string())
+            }
+            Err(error) => return Err(error),
+        };
+        current_elements_stack.truncate(stack_size);
+        break end;
+    };
+    println!(
+        "{:>width$}Loop ended after {} iterations: {end}",
+        "",
+        guard.iterations(),
+        width = depth * config::TAB_SIZE
+    );
+    Ok(())
 }
 
 impl WorkflowStep {
-    /// Execute a WorkflowStep
+    /// Execute a WorkflowStep, located at given path within the workflow,
+    /// handling its errors by its `on_error` policy (see `ErrorPolicy`), or
+    /// else by the one of the workflow.
+    #[allow(clippy::too_many_arguments)]
     #[async_recursion::async_recursion]
     pub async fn execute(
         &self,
         config: &config::Config,
         conn_webdriver: &fantoccini::Client,
         current_elements_stack: &mut Vec<Vec<fantoccini::elements::Element>>, // whole current_elements stack
-        current_values: &mut Vec<String>, // whole current_values stack
+        value_store: &mut ValueStore,                                         // values saved so far
+        soft_failures: &mut Vec<SquirrelError>, // failed soft assertions, and errors let through
+        breaks: &[BreakOn],                     // errors the enclosing loops break on
         depth: usize,
-        wf_name: &String,
-    ) -> Result<(), fantoccini::error::CmdError> {
-        let depth = depth + 1;
-        match self {
-            WorkflowStep::PageLocateElements(css, mode, index) => {
-                // Let's create new set of current_elements from found elements.
-                let mut elements = conn_webdriver
-                    .find_all(fantoccini::Locator::Css(css))
-                    .await?;
-                if mode == "all" {
-                    current_elements_stack.push(elements);
-                } else if mode == "index" {
-                    // running in "index" mode, so index value must be provided
-                    if elements.len() == 0 {
-                        // No element could be found with given selector.
-                        // Must raise error to signal the loop (if running)
-                        // in loop.
-                        return Err(fantoccini::error::CmdError::NotJson(
-                            "CustomError: Element not found!".to_string(),
-                        ));
-                    }
-                    let mut current_elements_local = vec![];
-                    current_elements_local.push(elements.remove(*index));
-                    current_elements_stack.push(current_elements_local);
-                } else {
-                    println!(
-                        "{:>width$}Incorrect arguments!",
-                        "",
-                        width = depth * config::TAB_SIZE
-                    )
+        wf: &workflow::Workflow,
+        path: &StepPath,
+    ) -> Result<(), SquirrelError> {
+        let mut retries = 0;
+        loop {
+            let stack_size = current_elements_stack.len();
+            let result = self
+                .execute_once(
+                    config,
+                    conn_webdriver,
+                    current_elements_stack,
+                    value_store,
+                    soft_failures,
+                    breaks,
+                    depth,
+                    wf,
+                    path,
+                )
+                .await;
+            let error = match result {
+                Ok(()) => return Ok(()),
+                // Breaking the loop (or skipping its iteration) is up to the enclosing loop
+                Err(error @ (SquirrelError::Break { .. } | SquirrelError::Skip { .. })) => {
+                    return Err(error)
                 }
-                let current_elements_len = current_elements_stack
-                    .get(current_elements_stack.len() - 1)
-                    .expect("Workflow Definition Error: `current_elements_stack` is empty!")
-                    .len();
+                Err(error) => error,
+            };
+            // Drop the elements located by the failing step, if any
+            current_elements_stack.truncate(stack_size);
+            let policy = self.error_policy(&error, wf, breaks);
+            let width = (depth + 1) * config::TAB_SIZE;
+            match policy {
+                ErrorPolicy::Fail => return Err(error),
+                ErrorPolicy::Continue => {
+                    println!("{:>width$}Step failed, continuing: {error}", "");
+                    soft_failures.push(error);
+                    return Ok(());
+                }
+                ErrorPolicy::SkipIteration => {
+                    return Err(SquirrelError::Skip {
+                        path: path.clone(),
+                        source: Box::new(error),
+                    })
+                }
+                ErrorPolicy::Retry { .. } => match policy.retry_delay(retries) {
+                    Some(delay) => {
+                        retries += 1;
+                        println!(
+                            "{:>width$}Step failed, retry {retries} in {}ms: {error}",
+                            "",
+                            delay.as_millis()
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                    None => return Err(error),
+                },
+            }
+        }
+    }
+
+    /// Return the policy for given error of the step: its own `on_error`, or
+    /// else the one of the workflow, unless the error ends an enclosing loop
+    /// (see `break_on`), such as not finding the "Next" element of `PageLoop`.
+    fn error_policy<'a>(
+        &'a self,
+        error: &SquirrelError,
+        wf: &'a workflow::Workflow,
+        breaks: &[BreakOn],
+    ) -> &'a ErrorPolicy {
+        match self.on_error() {
+            Some(policy) => policy,
+            // The errors of the loops (and of `If`) are the ones of their
+            // sub-steps, which are already handled by the policy of these
+            None if self.is_compound() => &ErrorPolicy::Fail,
+            None if breaks.iter().any(|kind| kind.matches(error)) => &ErrorPolicy::Fail,
+            None => &wf.on_error,
+        }
+    }
+
+    /// Execute the step once, regardless of its error policy.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_once(
+        &self,
+        config: &config::Config,
+        conn_webdriver: &fantoccini::Client,
+        current_elements_stack: &mut Vec<Vec<fantoccini::elements::Element>>,
+        value_store: &mut ValueStore,
+        soft_failures: &mut Vec<SquirrelError>,
+        breaks: &[BreakOn],
+        depth: usize,
+        wf: &workflow::Workflow,
+        path: &StepPath,
+    ) -> Result<(), SquirrelError> {
+        // Replace the variables referred to by the string arguments, if any
+        let lookup = |name: &str| template::lookup(name, value_store, &config.vars, &wf.vars);
+        let rendered = template::render(self, lookup, path)?;
+        let step = rendered.as_ref().unwrap_or(self);
+        if config.debug_mode && rendered.is_some() {
+            println!(
+                "{:>width$}[DEBUG] Rendered Step: {}",
+                "",
+                step.to_string(),
+                width = (depth + 1) * config::TAB_SIZE
+            );
+        }
+        let result = step
+            .execute_step(
+                config,
+                conn_webdriver,
+                current_elements_stack,
+                value_store,
+                soft_failures,
+                breaks,
+                depth,
+                wf,
+                path,
+            )
+            .await
+            .map_err(|error| error.at(path));
+        match result {
+            // In soft-assert mode, failed assertions are reported at the end of the run
+            Err(error @ SquirrelError::Assertion { .. })
+                if wf.soft_assertions && self.is_assertion() =>
+            {
                 println!(
-                    "{:>width$}Current Elements Stack Size: {}, Current Elements Size: {}",
+                    "{:>width$}Soft assertion failed: {error}",
                     "",
-                    current_elements_stack.len(),
-                    current_elements_len,
-                    width = depth * config::TAB_SIZE
+                    width = (depth + 1) * config::TAB_SIZE
                 );
-                if config::DEBUG_MODE {
-                    println!(
-                        "{:>width$}[DEBUG] Current Elements Stack: {:?}",
-                        "",
-                        current_elements_stack,
-                        width = depth * config::TAB_SIZE
-                    );
-                }
+                soft_failures.push(error);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_step(
+        &self,
+        config: &config::Config,
+        conn_webdriver: &fantoccini::Client,
+        current_elements_stack: &mut Vec<Vec<fantoccini::elements::Element>>,
+        value_store: &mut ValueStore,
+        soft_failures: &mut Vec<SquirrelError>,
+        breaks: &[BreakOn],
+        depth: usize,
+        wf: &workflow::Workflow,
+        path: &StepPath,
+    ) -> Result<(), SquirrelError> {
+        let depth = depth + 1;
+        match self {
+            WorkflowStep::PageLocateElements(args) => {
+                // Let's create new set of current_elements from found elements.
+                let find = || args.locator.find_all(conn_webdriver);
+                let elements = locate_elements(find, args, wf, conn_webdriver, depth, path).await?;
+                push_elements(elements, current_elements_stack, config, depth);
+                Ok(())
+            }
+            WorkflowStep::ElementLocateChildren(args) => {
+                let current_elements = current_elements(current_elements_stack, path)?;
+                let elem = current_element(current_elements, path)?;
+                let find = || args.locator.find_all_in(elem);
+                let elements = locate_elements(find, args, wf, conn_webdriver, depth, path).await?;
+                push_elements(elements, current_elements_stack, config, depth);
                 Ok(())
             }
-            WorkflowStep::ElementsLoopThrough(sub_steps) => {
+            WorkflowStep::ElementsLoopThrough(step_args::ElementsLoopThroughArgs {
+                sub_steps,
+                record,
+                ..
+            }) => {
                 // Get reference to current elements
-                let mut current_elements_len = current_elements_stack
-                    .get(current_elements_stack.len() - 1)
-                    .expect("Workflow Definition Error: `current_elements_stack` is empty!")
-                    .len();
+                let mut current_elements_len =
+                    current_elements(current_elements_stack, path)?.len();
+                let (stack_size, iterations) = (current_elements_stack.len(), current_elements_len);
                 // Start the loop
-                while current_elements_len > 0 {
+                let end = loop {
+                    if current_elements_len == 0 {
+                        break LoopEnd::Completed;
+                    }
                     let index_elem = current_elements_len - 1;
                     println!(
                         "{:>width$}Element index {index_elem}:",
                         "",
                         width = depth * config::TAB_SIZE
                     );
-                    for (index_sub_step, sub_step) in sub_steps.iter().enumerate() {
+                    let result = execute_iteration(
+                        sub_steps,
+                        record,
+                        config,
+                        conn_webdriver,
+                        current_elements_stack,
+                        value_store,
+                        soft_failures,
+                        breaks,
+                        depth,
+                        wf,
+                        path,
+                    )
+                    .await;
+                    match result {
+                        Err(SquirrelError::Break { message, .. }) => break LoopEnd::Break(message),
+                        Err(SquirrelError::Skip { source, .. }) => {
+                            skip_iteration(*source, soft_failures, depth);
+                            // Drop the elements located by the iteration, along
+                            // with the current element, unless already popped
+                            current_elements_stack.truncate(stack_size);
+                            if let Some(current_elements) = current_elements_stack
+                                .last_mut()
+                                .filter(|elements| elements.len() == current_elements_len)
+                            {
+                                current_elements.pop();
+                            }
+                        }
+                        result => result?,
+                    }
+                    current_elements_len -= 1;
+                };
+                println!(
+                    "{:>width$}Loop ended after {} iterations: {end}",
+                    "",
+                    iterations - current_elements_len + usize::from(end != LoopEnd::Completed),
+                    width = depth * config::TAB_SIZE
+                );
+                // The top of the stack is now empty (or else no longer needed,
+                // along with the elements located by the iteration); remove it
+                current_elements_stack.truncate(stack_size - 1);
+                Ok(())
+            }
+            WorkflowStep::PageLoop(step_args::PageLoopArgs {
+                sub_steps,
+                record,
+                max_iterations,
+                max_duration_ms,
+                break_on,
+                ..
+            }) => {
+                // Run the loop until a sub-step fails with an error signalling
+                // the end (such as no longer able to find "Next" element in
+                // pagination while keep on clicking it), or a limit is reached
+                let guard = LoopGuard::new(*max_iterations, *max_duration_ms);
+                execute_loop(
+                    sub_steps,
+                    record,
+                    None,
+                    guard,
+                    break_on,
+                    config,
+                    conn_webdriver,
+                    current_elements_stack,
+                    value_store,
+                    soft_failures,
+                    breaks,
+                    depth,
+                    wf,
+                    path,
+                )
+                .await
+            }
+            WorkflowStep::Repeat(step_args::RepeatArgs {
+                times,
+                sub_steps,
+                record,
+                break_on,
+                ..
+            }) => {
+                let guard = LoopGuard::new(Some(*times), None);
+                execute_loop(
+                    sub_steps,
+                    record,
+                    None,
+                    guard,
+                    break_on,
+                    config,
+                    conn_webdriver,
+                    current_elements_stack,
+                    value_store,
+                    soft_failures,
+                    breaks,
+                    depth,
+                    wf,
+                    path,
+                )
+                .await
+            }
+            WorkflowStep::While(step_args::WhileArgs {
+                condition,
+                sub_steps,
+                record,
+                max_iterations,
+                max_duration_ms,
+                break_on,
+                ..
+            }) => {
+                let guard = LoopGuard::new(*max_iterations, *max_duration_ms);
+                execute_loop(
+                    sub_steps,
+                    record,
+                    Some(condition),
+                    guard,
+                    break_on,
+                    config,
+                    conn_webdriver,
+                    current_elements_stack,
+                    value_store,
+                    soft_failures,
+                    breaks,
+                    depth,
+                    wf,
+                    path,
+                )
+                .await
+            }
+            WorkflowStep::Try(step_args::TryArgs {
+                steps,
+                catch,
+                finally,
+                rethrow,
+                ..
+            }) => {
+                let stack_size = current_elements_stack.len();
+                let mut result = execute_sub_steps(
+                    steps,
+                    config,
+                    conn_webdriver,
+                    current_elements_stack,
+                    value_store,
+                    soft_failures,
+                    breaks,
+                    depth,
+                    wf,
+                    &path.child(0),
+                )
+                .await;
+                match result {
+                    // Breaking the loop (or skipping its iteration) is not an error to catch
+                    Err(SquirrelError::Break { .. } | SquirrelError::Skip { .. }) => {}
+                    Err(error) if !catch.is_empty() => {
                         println!(
-                            "{:>width$}SubStep {index_sub_step}: {} (timestamp={})",
+                            "{:>width$}Caught: {error}",
                             "",
-                            sub_step.to_string(),
-                            utils::timestamp(),
-                            width = (depth + 1) * config::TAB_SIZE,
+                            width = depth * config::TAB_SIZE
                         );
-                        sub_step
-                            .execute(
-                                config,
-                                conn_webdriver,
-                                current_elements_stack,
-                                current_values,
-                                depth + 1,
-                                wf_name,
-                            )
-                            .await?;
+                        current_elements_stack.truncate(stack_size);
+                        // The error is there for `catch` alone, even if a value is saved by its name
+                        value_store.begin_scope("error", error.to_string().into());
+                        result = execute_sub_steps(
+                            catch,
+                            config,
+                            conn_webdriver,
+                            current_elements_stack,
+                            value_store,
+                            soft_failures,
+                            breaks,
+                            depth,
+                            wf,
+                            &path.child(1),
+                        )
+                        .await;
+                        value_store.end_scope();
+                        result = result.and(if *rethrow { Err(error) } else { Ok(()) });
                     }
-                    current_elements_len -= 1;
+                    _ => {}
                 }
-                // The top of the stack is now empty; remove it
-                current_elements_stack.pop();
-                Ok(())
-            }
-            WorkflowStep::PageLoop(sub_steps) => {
-                let mut index_loop = 0;
-                // Run infinite loop until a subcommand fails, in which case
-                // exist out of this whole workflow step.
-                loop {
-                    println!(
-                        "{:>width$}Loop No. {index_loop}",
-                        "",
-                        width = depth * config::TAB_SIZE
-                    );
-                    index_loop += 1;
-                    for (index_sub_step, sub_step) in sub_steps.iter().enumerate() {
+                if result.is_err() {
+                    current_elements_stack.truncate(stack_size);
+                }
+                let finally_result = execute_sub_steps(
+                    finally,
+                    config,
+                    conn_webdriver,
+                    current_elements_stack,
+                    value_store,
+                    soft_failures,
+                    breaks,
+                    depth,
+                    wf,
+                    &path.child(2),
+                )
+                .await;
+                match (result, finally_result) {
+                    // Don't hide the error the sub-steps failed with
+                    (Err(error), Err(finally_error)) => {
                         println!(
-                            "{:>width$}SubStep {index_sub_step}: {} (timestamp={})",
+                            "{:>width$}Finally failed too: {finally_error}",
                             "",
-                            sub_step.to_string(),
-                            utils::timestamp(),
-                            width = (depth + 1) * config::TAB_SIZE,
+                            width = depth * config::TAB_SIZE
                         );
-                        // Note that if the sub-step raised an error (such as no
-                        // longer able to find "Next" element in pagination
-                        // while keep on clicking it), it is signal that this
-                        // infinite loop must be stopped.
-                        let result = sub_step
-                            .execute(
-                                config,
-                                conn_webdriver,
-                                current_elements_stack,
-                                current_values,
-                                depth + 1,
-                                wf_name,
-                            )
-                            .await;
-                        match result {
-                            Err(error) => {
-                                // time to end the loop
-                                println!(
-                                    "{:>width$}Ending the infinite loop due to error: {error}",
-                                    "",
-                                    width = (depth + 2) * config::TAB_SIZE
-                                );
-                                return Ok(());
-                            }
-                            Ok(_) => {
-                                // all good
-                            }
-                        }
+                        Err(error)
                     }
+                    (result, finally_result) => result.and(finally_result),
+                }
+            }
+            WorkflowStep::BreakIfMissing(step_args::BreakIfMissingArgs { locator, .. }) => {
+                if locator.find_all(conn_webdriver).await?.is_empty() {
+                    return Err(SquirrelError::Break {
+                        path: path.clone(),
+                        message: format!("No element matching {locator}"),
+                    });
                 }
-                // this is unreachable code
+                Ok(())
             }
-            WorkflowStep::ElementPop => {
+            WorkflowStep::If(step_args::IfArgs {
+                condition,
+                then,
+                else_steps,
+                ..
+            }) => {
+                // The sub-steps are left as they are (see `template::render`),
+                // but the condition is rendered right before it is checked
+                let lookup =
+                    |name: &str| template::lookup(name, value_store, &config.vars, &wf.vars);
+                let rendered = template::render_args(condition, lookup, path)?;
+                let condition = rendered.as_ref().unwrap_or(condition);
+                let holds = condition.check(conn_webdriver, value_store, path).await?;
+                println!(
+                    "{:>width$}Condition {condition}: {holds}",
+                    "",
+                    width = depth * config::TAB_SIZE
+                );
+                let (branch, sub_steps) = if holds { (0, then) } else { (1, else_steps) };
+                execute_sub_steps(
+                    sub_steps,
+                    config,
+                    conn_webdriver,
+                    current_elements_stack,
+                    value_store,
+                    soft_failures,
+                    breaks,
+                    depth,
+                    wf,
+                    &path.child(branch),
+                )
+                .await
+            }
+            WorkflowStep::ElementPop(..) => {
                 // Get reference to current elements
-                let current_elements_stack_len = current_elements_stack.len();
-                let current_elements = current_elements_stack
-                    .get_mut(current_elements_stack_len - 1)
-                    .expect("Workflow Definition Error: `current_elements_stack` is empty!");
+                let current_elements =
+                    current_elements_stack
+                        .last_mut()
+                        .ok_or_else(|| SquirrelError::Validation {
+                            path: path.clone(),
+                            message: EMPTY_STACK_MESSAGE.to_string(),
+                        })?;
 
-                if config::DEBUG_MODE {
+                if config.debug_mode {
                     println!(
                         "{:>width$}[DEBUG] Current Elements Size (before): {}",
                         "",
@@ -290,7 +1315,7 @@ impl WorkflowStep {
                     );
                 }
                 current_elements.pop();
-                if config::DEBUG_MODE {
+                if config.debug_mode {
                     println!(
                         "{:>width$}[DEBUG] Current Elements Size (after): {}",
                         "",
@@ -306,30 +1331,81 @@ impl WorkflowStep {
                 }
                 Ok(())
             }
-            WorkflowStep::ElementSaveHtmlValue(name, is_inner) => {
+            WorkflowStep::ElementSaveHtmlValue(step_args::ElementSaveHtmlValueArgs {
+                name,
+                is_inner,
+                transforms,
+                ..
+            }) => {
                 // Get reference to the current element
-                let current_elements = current_elements_stack
-                    .get(current_elements_stack.len() - 1)
-                    .expect("Workflow Definition Error: `current_elements_stack` is empty!");
-                let current_elem = current_elements
-                    .get(current_elements.len() - 1)
-                    .expect("Workflow Definition Error: `current_elements` is empty!");
+                let current_elements = current_elements(current_elements_stack, path)?;
+                let current_elem = current_element(current_elements, path)?;
                 // Get its HTML value
                 let elem_html = current_elem.html(*is_inner).await?;
-                let elem_value = format!("{name}::{elem_html}");
-                println!(
-                    "{:>width$}HTML Value: {}",
-                    "",
-                    elem_value,
-                    width = depth * config::TAB_SIZE
-                );
                 // Save element's value
-                current_values.push(elem_value);
-                Ok(())
+                save_value(value_store, name, elem_html.into(), transforms, depth, path)
+            }
+            WorkflowStep::ElementSaveText(step_args::SaveValueArgs {
+                name, transforms, ..
+            }) => {
+                let current_elements = current_elements(current_elements_stack, path)?;
+                let text = current_element(current_elements, path)?.text().await?;
+                save_value(value_store, name, text.into(), transforms, depth, path)
+            }
+            WorkflowStep::ElementSaveAttribute(step_args::ElementSaveAttributeArgs {
+                name,
+                attr,
+                transforms,
+                ..
+            }) => {
+                let current_elements = current_elements(current_elements_stack, path)?;
+                let value = current_element(current_elements, path)?.attr(attr).await?;
+                save_value(value_store, name, value.into(), transforms, depth, path)
+            }
+            WorkflowStep::ElementSaveProperty(step_args::ElementSavePropertyArgs {
+                name,
+                prop,
+                transforms,
+                ..
+            }) => {
+                let current_elements = current_elements(current_elements_stack, path)?;
+                let value = current_element(current_elements, path)?.prop(prop).await?;
+                save_value(value_store, name, value.into(), transforms, depth, path)
+            }
+            WorkflowStep::ElementSaveCssValue(step_args::ElementSaveCssValueArgs {
+                name,
+                property,
+                transforms,
+                ..
+            }) => {
+                let current_elements = current_elements(current_elements_stack, path)?;
+                let value = current_element(current_elements, path)?
+                    .css_value(property)
+                    .await?;
+                save_value(value_store, name, value.into(), transforms, depth, path)
+            }
+            WorkflowStep::PageSaveUrl(step_args::SaveValueArgs {
+                name, transforms, ..
+            }) => {
+                let url = conn_webdriver.current_url().await?;
+                save_value(
+                    value_store,
+                    name,
+                    url.as_str().into(),
+                    transforms,
+                    depth,
+                    path,
+                )
+            }
+            WorkflowStep::PageSaveTitle(step_args::SaveValueArgs {
+                name, transforms, ..
+            }) => {
+                let title = conn_webdriver.title().await?;
+                save_value(value_store, name, title.into(), transforms, depth, path)
             }
-            WorkflowStep::PageOpen(url) => {
+            WorkflowStep::PageOpen(step_args::PageOpenArgs { url, .. }) => {
                 conn_webdriver
-                    .set_window_size(config::WINDOW_WIDTH_DEFAULT, config::WINDOW_HEIGHT_DEFAULT)
+                    .set_window_size(config.window_width, config.window_height)
                     .await?;
                 conn_webdriver.goto(url).await?;
                 let size = conn_webdriver.get_window_size().await?;
@@ -341,7 +1417,7 @@ impl WorkflowStep {
                 );
                 Ok(())
             }
-            WorkflowStep::PageRefresh => {
+            WorkflowStep::PageRefresh(..) => {
                 conn_webdriver.refresh().await?;
                 let size = conn_webdriver.get_window_size().await?;
                 println!(
@@ -352,62 +1428,74 @@ impl WorkflowStep {
                 );
                 Ok(())
             }
-            WorkflowStep::PageBack => {
+            WorkflowStep::PageBack(..) => {
                 conn_webdriver.back().await?;
                 Ok(())
             }
-            WorkflowStep::PageBackWindow => {
+            WorkflowStep::PageBackWindow(..) => {
                 conn_webdriver.close_window().await?;
                 // Note: ElementClickNewWindow always needs to be paired with PageBackWindow
-                let prev_window = conn_webdriver.windows().await?.pop().ok_or(
-                    fantoccini::error::CmdError::NotJson(
-                        "ElementClickNewWindow always needs to be paired with PageBackWindow"
-                            .to_string(),
-                    ),
-                )?;
+                let prev_window =
+                    conn_webdriver
+                        .windows()
+                        .await?
+                        .pop()
+                        .ok_or(SquirrelError::Validation {
+                        path: path.clone(),
+                        message:
+                            "ElementClickNewWindow always needs to be paired with PageBackWindow"
+                                .to_string(),
+                    })?;
                 conn_webdriver.switch_to_window(prev_window).await?;
                 Ok(())
             }
 
-            WorkflowStep::PageScroll(mode, page_size) => {
-                // Note: Not making use of mode yet
-                let mut arguments = Vec::new();
-                let argument = serde_json::json!(page_size);
-                arguments.push(argument);
-                if (mode == "full") && (*page_size == 1.0) {
-                    conn_webdriver
-                        .execute("window.scrollTo(0, document.body.scrollHeight)", arguments)
-                        .await?;
-                } else if (mode == "full") && (*page_size == -1.0) {
-                    conn_webdriver
-                        .execute("window.scrollTo(0, 0)", arguments)
-                        .await?;
-                } else if mode == "page" {
-                    conn_webdriver
-                        .execute(
-                            "window.scrollBy(0, arguments[0] * window.innerHeight)",
-                            arguments,
-                        )
-                        .await?;
-                } else {
-                    println!(
-                        "{:>width$}Incorrect arguments!",
-                        "",
-                        width = depth * config::TAB_SIZE
-                    )
-                }
+            WorkflowStep::PageScroll(step_args::PageScrollArgs {
+                mode, page_size, ..
+            }) => {
+                let mut arguments = vec![serde_json::json!(page_size)];
+                let script = match mode {
+                    step_args::ScrollMode::Full if *page_size == 1.0 => {
+                        "window.scrollTo(0, document.body.scrollHeight)"
+                    }
+                    step_args::ScrollMode::Full if *page_size == -1.0 => "window.scrollTo(0, 0)",
+                    step_args::ScrollMode::Full => {
+                        return Err(SquirrelError::Validation {
+                            path: path.clone(),
+                            message: format!(
+                                "Incorrect page_size={page_size} of mode \"full\", expected 1.0 or -1.0"
+                            ),
+                        })
+                    }
+                    step_args::ScrollMode::Page => {
+                        "window.scrollBy(0, arguments[0] * window.innerHeight)"
+                    }
+                    step_args::ScrollMode::ToElement => {
+                        let current_elements = current_elements(current_elements_stack, path)?;
+                        let elem = current_element(current_elements, path)?;
+                        arguments = vec![serde_json::json!(elem)];
+                        "arguments[0].scrollIntoView({block: 'center'})"
+                    }
+                    step_args::ScrollMode::Pixels => "window.scrollBy(0, arguments[0])",
+                    step_args::ScrollMode::Horizontal => {
+                        "window.scrollBy(arguments[0] * window.innerWidth, 0)"
+                    }
+                };
+                conn_webdriver.execute(script, arguments).await?;
                 Ok(())
             }
-            WorkflowStep::PageTakeScreenshot(file_prefix) => {
-                let file_name = format!("{wf_name}_{file_prefix}_{}.png", utils::timestamp());
+            WorkflowStep::PageTakeScreenshot(step_args::TakeScreenshotArgs {
+                file_prefix, ..
+            }) => {
+                let file_name = format!("{}_{file_prefix}_{}.png", wf.name, utils::timestamp());
                 let data = conn_webdriver.screenshot().await?;
                 utils::write_file(&config.temp_dir, &file_name, &data)?;
                 Ok(())
             }
-            WorkflowStep::PageWait(duration_ms) => {
+            WorkflowStep::PageWait(step_args::PageWaitArgs { duration_ms, .. }) => {
                 // reduce the wait time if running in remote mode
                 let duration_ms = if config.headless_browser {
-                    config::REMOTE_WAIT_FACTOR * (*duration_ms as f64)
+                    config.remote_wait_factor * (*duration_ms as f64)
                 } else {
                     *duration_ms as f64
                 }
@@ -421,20 +1509,93 @@ impl WorkflowStep {
                 tokio::time::sleep(std::time::Duration::from_millis(duration_ms)).await;
                 Ok(())
             }
-            WorkflowStep::ElementClick(check_enabled, check_url) => {
-                let current_elements = current_elements_stack
-                    .get(current_elements_stack.len() - 1)
-                    .expect("Workflow Definition Error: `current_elements_stack` is empty!");
-                let elem = current_elements
-                    .get(current_elements.len() - 1)
-                    .expect("Workflow Definition Error: `current_element` is empty!");
+            WorkflowStep::WaitForElement(step_args::WaitForElementArgs {
+                locator,
+                state,
+                timeout_ms,
+                interval_ms,
+                ..
+            }) => {
+                let condition = format!("elements matching {locator} to be {state}");
+                wait::poll_until(*timeout_ms, *interval_ms, &condition, || async move {
+                    let elements = locator.find_all(conn_webdriver).await?;
+                    Ok(wait::is_in_state(&elements, state).await?)
+                })
+                .await
+            }
+            WorkflowStep::WaitForUrl(step_args::WaitForUrlArgs {
+                pattern,
+                timeout_ms,
+                interval_ms,
+                ..
+            }) => {
+                let regex =
+                    regex::Regex::new(pattern).map_err(|error| SquirrelError::Validation {
+                        path: path.clone(),
+                        message: format!("Invalid pattern: {error}"),
+                    })?;
+                let condition = format!("URL to match {pattern:?}");
+                wait::poll_until(*timeout_ms, *interval_ms, &condition, || async {
+                    let url = conn_webdriver.current_url().await?;
+                    Ok(regex.is_match(url.as_str()))
+                })
+                .await
+            }
+            WorkflowStep::WaitForText(step_args::WaitForTextArgs {
+                text,
+                locator,
+                timeout_ms,
+                interval_ms,
+                ..
+            }) => {
+                let body = Locator::Css(String::from("body"));
+                let locator = locator.as_ref().unwrap_or(&body);
+                let condition = format!("text {text:?} within elements matching {locator}");
+                wait::poll_until(*timeout_ms, *interval_ms, &condition, || async move {
+                    for elem in locator.find_all(conn_webdriver).await? {
+                        match elem.text().await {
+                            Ok(elem_text) if elem_text.contains(text.as_str()) => return Ok(true),
+                            Ok(_) => {}
+                            // the element got removed from the page meanwhile
+                            Err(error) if wait::is_stale(&error) => {}
+                            Err(error) => return Err(error.into()),
+                        }
+                    }
+                    Ok(false)
+                })
+                .await
+            }
+            WorkflowStep::WaitForJs(step_args::WaitForJsArgs {
+                expression,
+                timeout_ms,
+                interval_ms,
+                ..
+            }) => {
+                let script = format!("return !!({expression});");
+                let condition = format!("expression `{expression}` to be truthy");
+                wait::poll_until(*timeout_ms, *interval_ms, &condition, || async {
+                    let value = conn_webdriver.execute(&script, Vec::new()).await?;
+                    Ok(value == serde_json::Value::Bool(true))
+                })
+                .await
+            }
+            WorkflowStep::ElementClick(step_args::ElementClickArgs {
+                check_enabled,
+                check_url,
+                ..
+            }) => {
+                let current_elements = current_elements(current_elements_stack, path)?;
+                let elem = current_element(current_elements, path)?;
                 // Make sure the element is enabled if `check_enabled` is `true`
                 if *check_enabled {
                     let status = elem.is_enabled().await?;
                     if !status {
-                        return Err(fantoccini::error::CmdError::NotJson(
-                            "Element is Disabled".to_string(),
-                        ));
+                        return Err(SquirrelError::Assertion {
+                            path: path.clone(),
+                            message: "Element is Disabled".to_string(),
+                            expected: "enabled".to_string(),
+                            actual: "disabled".to_string(),
+                        });
                     }
                 }
                 // Get URL before the click
@@ -451,25 +1612,26 @@ impl WorkflowStep {
                     width = depth * config::TAB_SIZE
                 );
                 // Make sure current_url is changed if `check_url` is `true`
-                if *check_url {
-                    if b_url == a_url {
-                        return Err(fantoccini::error::CmdError::NotJson(
-                            "No change in URL".to_string(),
-                        ));
-                    }
+                if *check_url && b_url == a_url {
+                    return Err(SquirrelError::Assertion {
+                        path: path.clone(),
+                        message: "No change in URL".to_string(),
+                        expected: format!("a URL other than {b_url}"),
+                        actual: a_url.to_string(),
+                    });
                 }
                 Ok(())
             }
-            WorkflowStep::ElementClickNewWindow => {
-                let current_elements = current_elements_stack
-                    .get(current_elements_stack.len() - 1)
-                    .expect("Workflow Definition Error: `current_elements_stack` is empty!");
-                let elem = current_elements
-                    .get(current_elements.len() - 1)
-                    .expect("Workflow Definition Error: `current_element` is empty!");
-                let href = elem.attr("href").await?.expect(
-                    "Workflow Definition Error: Selected elements doesn't have 'href' attribute!",
-                );
+            WorkflowStep::ElementClickNewWindow(..) => {
+                let current_elements = current_elements(current_elements_stack, path)?;
+                let elem = current_element(current_elements, path)?;
+                let href = elem
+                    .attr("href")
+                    .await?
+                    .ok_or_else(|| SquirrelError::Validation {
+                        path: path.clone(),
+                        message: "Selected element doesn't have 'href' attribute".to_string(),
+                    })?;
                 println!(
                     "{:>width$}Current Element HREF: {}",
                     "",
@@ -479,7 +1641,7 @@ impl WorkflowStep {
                 let new_window = conn_webdriver.new_window(true).await?.handle;
                 conn_webdriver.switch_to_window(new_window).await?;
                 conn_webdriver
-                    .set_window_size(config::WINDOW_WIDTH_DEFAULT, config::WINDOW_HEIGHT_DEFAULT)
+                    .set_window_size(config.window_width, config.window_height)
                     .await?;
                 conn_webdriver.goto(&href).await?;
                 let size = conn_webdriver.get_window_size().await?;
@@ -491,23 +1653,18 @@ impl WorkflowStep {
                 );
                 Ok(())
             }
-            WorkflowStep::ElementSendKeys(keys) => {
-                let current_elements = current_elements_stack
-                    .get(current_elements_stack.len() - 1)
-                    .expect("Workflow Definition Error: `current_elements_stack` is empty!");
-                let elem = current_elements
-                    .get(current_elements.len() - 1)
-                    .expect("Workflow Definition Error: `current_element` is empty!");
+            WorkflowStep::ElementSendKeys(step_args::ElementSendKeysArgs { keys, .. }) => {
+                let current_elements = current_elements(current_elements_stack, path)?;
+                let elem = current_element(current_elements, path)?;
                 elem.send_keys(keys).await?;
                 Ok(())
             }
-            WorkflowStep::ElementTakeScreenshot(file_prefix) => {
-                let current_elements = current_elements_stack
-                    .get(current_elements_stack.len() - 1)
-                    .expect("Workflow Definition Error: `current_elements_stack` is empty!");
-                let elem = current_elements
-                    .get(current_elements.len() - 1)
-                    .expect("Workflow Definition Error: `current_element` is empty!");
+            WorkflowStep::ElementTakeScreenshot(step_args::TakeScreenshotArgs {
+                file_prefix,
+                ..
+            }) => {
+                let current_elements = current_elements(current_elements_stack, path)?;
+                let elem = current_element(current_elements, path)?;
                 let len = current_elements.len();
                 let file_name = format!("{file_prefix}_{}_{len}.png", utils::timestamp());
                 match elem.screenshot().await {
@@ -526,58 +1683,476 @@ impl WorkflowStep {
                 };
                 Ok(())
             }
-            WorkflowStep::PrintCurrentValues => {
-                for (i, value) in current_values.iter().enumerate() {
-                    let tup = (i, value);
+            WorkflowStep::AssertElementCount(step_args::AssertElementCountArgs {
+                locator,
+                count,
+                ..
+            }) => {
+                let actual = locator.find_all(conn_webdriver).await?.len();
+                if actual != *count {
+                    let subject = format!("Number of elements {locator}");
+                    return Err(assertion::failure(&subject, count, actual, path));
+                }
+                Ok(())
+            }
+            WorkflowStep::AssertText(step_args::AssertTextArgs {
+                expected, locator, ..
+            }) => {
+                let elem =
+                    target_element(locator, conn_webdriver, current_elements_stack, path).await?;
+                let subject = format!("Text of {}", describe_target(locator));
+                expected.check(&subject, &elem.text().await?, path)
+            }
+            WorkflowStep::AssertAttribute(step_args::AssertAttributeArgs {
+                name,
+                expected,
+                locator,
+                ..
+            }) => {
+                let elem =
+                    target_element(locator, conn_webdriver, current_elements_stack, path).await?;
+                let subject = format!("Attribute {name:?} of {}", describe_target(locator));
+                match elem.attr(name).await? {
+                    Some(actual) => expected.check(&subject, &actual, path),
+                    None => Err(assertion::failure(&subject, expected, "(missing)", path)),
+                }
+            }
+            WorkflowStep::AssertUrl(step_args::AssertPageArgs { expected, .. }) => {
+                let url = conn_webdriver.current_url().await?;
+                expected.check("URL of the page", url.as_str(), path)
+            }
+            WorkflowStep::AssertTitle(step_args::AssertPageArgs { expected, .. }) => {
+                let title = conn_webdriver.title().await?;
+                expected.check("Title of the page", &title, path)
+            }
+            WorkflowStep::AssertVisible(step_args::AssertVisibleArgs { locator, .. }) => {
+                let is_visible = match locator {
+                    Some(locator) => {
+                        let elements = locator.find_all(conn_webdriver).await?;
+                        wait::is_in_state(&elements, &step_args::ElementState::Visible).await?
+                    }
+                    None => {
+                        let current_elements = current_elements(current_elements_stack, path)?;
+                        current_element(current_elements, path)?
+                            .is_displayed()
+                            .await?
+                    }
+                };
+                if !is_visible {
+                    let subject = format!("Visibility of {}", describe_target(locator));
+                    return Err(assertion::failure(&subject, "visible", "hidden", path));
+                }
+                Ok(())
+            }
+            WorkflowStep::AssertValue(step_args::AssertValueArgs { name, expected, .. }) => {
+                let subject = format!("Value {name:?}");
+                match value_store.get_text(name) {
+                    Some(actual) => expected.check(&subject, &actual, path),
+                    None => Err(assertion::failure(&subject, expected, "(not saved)", path)),
+                }
+            }
+            WorkflowStep::PrintCurrentValues(..) => {
+                let values = serde_json::Value::Object(value_store.root().clone());
+                println!(
+                    "{:>width$}Values: {values}",
+                    "",
+                    width = depth * config::TAB_SIZE
+                );
+                if value_store.current() != value_store.root() {
+                    let record = serde_json::Value::Object(value_store.current().clone());
                     println!(
-                        "{:>width$}Value: {:?}",
+                        "{:>width$}Current Record: {record}",
                         "",
-                        tup,
                         width = depth * config::TAB_SIZE
                     );
                 }
                 Ok(())
             }
+            WorkflowStep::ExportValues(step_args::ExportValuesArgs {
+                format,
+                file_prefix,
+                records,
+                ..
+            }) => {
+                let file_name = export::write(
+                    &config.temp_dir,
+                    &wf.name,
+                    file_prefix,
+                    value_store.root(),
+                    *format,
+                    records.as_deref(),
+                    path,
+                )?;
+                println!(
+                    "{:>width$}Exported values to: {file_name}",
+                    "",
+                    width = depth * config::TAB_SIZE
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Return `true` if the step executes other steps (such as the loops).
+    pub fn is_compound(&self) -> bool {
+        matches!(
+            self,
+            WorkflowStep::ElementsLoopThrough(..)
+                | WorkflowStep::PageLoop(..)
+                | WorkflowStep::Repeat(..)
+                | WorkflowStep::While(..)
+                | WorkflowStep::If(..)
+                | WorkflowStep::Try(..)
+        )
+    }
+
+    /// Return the policy for when the step fails, if given by the step itself.
+    pub fn on_error(&self) -> Option<&ErrorPolicy> {
+        match self {
+            WorkflowStep::PageLocateElements(step_args::LocateElementsArgs {
+                on_error, ..
+            })
+            | WorkflowStep::ElementLocateChildren(step_args::LocateElementsArgs {
+                on_error, ..
+            })
+            | WorkflowStep::ElementSaveHtmlValue(step_args::ElementSaveHtmlValueArgs {
+                on_error,
+                ..
+            })
+            | WorkflowStep::ElementSaveText(step_args::SaveValueArgs { on_error, .. })
+            | WorkflowStep::ElementSaveAttribute(step_args::ElementSaveAttributeArgs {
+                on_error,
+                ..
+            })
+            | WorkflowStep::ElementSaveProperty(step_args::ElementSavePropertyArgs {
+                on_error,
+                ..
+            })
+            | WorkflowStep::ElementSaveCssValue(step_args::ElementSaveCssValueArgs {
+                on_error,
+                ..
+            })
+            | WorkflowStep::PageSaveUrl(step_args::SaveValueArgs { on_error, .. })
+            | WorkflowStep::PageSaveTitle(step_args::SaveValueArgs { on_error, .. })
+            | WorkflowStep::PageOpen(step_args::PageOpenArgs { on_error, .. })
+            | WorkflowStep::PageScroll(step_args::PageScrollArgs { on_error, .. })
+            | WorkflowStep::PageTakeScreenshot(step_args::TakeScreenshotArgs {
+                on_error, ..
+            })
+            | WorkflowStep::PageWait(step_args::PageWaitArgs { on_error, .. })
+            | WorkflowStep::WaitForElement(step_args::WaitForElementArgs { on_error, .. })
+            | WorkflowStep::WaitForUrl(step_args::WaitForUrlArgs { on_error, .. })
+            | WorkflowStep::WaitForText(step_args::WaitForTextArgs { on_error, .. })
+            | WorkflowStep::WaitForJs(step_args::WaitForJsArgs { on_error, .. })
+            | WorkflowStep::ElementClick(step_args::ElementClickArgs { on_error, .. })
+            | WorkflowStep::ElementSendKeys(step_args::ElementSendKeysArgs { on_error, .. })
+            | WorkflowStep::ElementTakeScreenshot(step_args::TakeScreenshotArgs {
+                on_error, ..
+            })
+            | WorkflowStep::AssertElementCount(step_args::AssertElementCountArgs {
+                on_error,
+                ..
+            })
+            | WorkflowStep::AssertText(step_args::AssertTextArgs { on_error, .. })
+            | WorkflowStep::AssertAttribute(step_args::AssertAttributeArgs { on_error, .. })
+            | WorkflowStep::AssertUrl(step_args::AssertPageArgs { on_error, .. })
+            | WorkflowStep::AssertTitle(step_args::AssertPageArgs { on_error, .. })
+            | WorkflowStep::AssertVisible(step_args::AssertVisibleArgs { on_error, .. })
+            | WorkflowStep::AssertValue(step_args::AssertValueArgs { on_error, .. })
+            | WorkflowStep::ExportValues(step_args::ExportValuesArgs { on_error, .. })
+            | WorkflowStep::ElementsLoopThrough(step_args::ElementsLoopThroughArgs {
+                on_error,
+                ..
+            })
+            | WorkflowStep::PageLoop(step_args::PageLoopArgs { on_error, .. })
+            | WorkflowStep::Repeat(step_args::RepeatArgs { on_error, .. })
+            | WorkflowStep::While(step_args::WhileArgs { on_error, .. })
+            | WorkflowStep::BreakIfMissing(step_args::BreakIfMissingArgs { on_error, .. })
+            | WorkflowStep::If(step_args::IfArgs { on_error, .. })
+            | WorkflowStep::Try(step_args::TryArgs { on_error, .. })
+            | WorkflowStep::ElementPop(step_args::NoArgs { on_error })
+            | WorkflowStep::PageRefresh(step_args::NoArgs { on_error })
+            | WorkflowStep::PageBack(step_args::NoArgs { on_error })
+            | WorkflowStep::PageBackWindow(step_args::NoArgs { on_error })
+            | WorkflowStep::ElementClickNewWindow(step_args::NoArgs { on_error })
+            | WorkflowStep::PrintCurrentValues(step_args::NoArgs { on_error }) => on_error.as_ref(),
         }
     }
 
+    /// Return `true` if the step is an assertion (such as `AssertText`), whose
+    /// failure is soft with `soft_assertions` of the workflow.
+    pub fn is_assertion(&self) -> bool {
+        matches!(
+            self,
+            WorkflowStep::AssertElementCount(..)
+                | WorkflowStep::AssertText(..)
+                | WorkflowStep::AssertAttribute(..)
+                | WorkflowStep::AssertUrl(..)
+                | WorkflowStep::AssertTitle(..)
+                | WorkflowStep::AssertVisible(..)
+                | WorkflowStep::AssertValue(..)
+        )
+    }
+
+    /// Return the name and documentation of all the supported steps.
+    pub fn catalog() -> Vec<(String, String)> {
+        WorkflowStep::iter()
+            .map(|step| {
+                let documentation = step.get_documentation().unwrap_or_default();
+                (format!("{step}"), documentation.trim_end().to_string())
+            })
+            .collect()
+    }
+
     /// Provide string representation of a WorkflowStep
     /// Overriding what's derived from strum_macros::Display
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(&self) -> String {
-        match self {
-            WorkflowStep::PageLocateElements(css, mode, index) => {
-                format!("{self} by css={css} in mode={mode} at index={index}")
+        let text = match self {
+            WorkflowStep::PageLocateElements(step_args::LocateElementsArgs {
+                locator,
+                mode,
+                index,
+                ..
+            })
+            | WorkflowStep::ElementLocateChildren(step_args::LocateElementsArgs {
+                locator,
+                mode,
+                index,
+                ..
+            }) => {
+                format!("{self} by {locator} in mode={mode} at index={index}")
             }
-            WorkflowStep::ElementsLoopThrough(sub_steps) => {
+            WorkflowStep::ElementsLoopThrough(step_args::ElementsLoopThroughArgs {
+                sub_steps,
+                ..
+            }) => {
                 format!("{self} with sub_steps: {:?}", sub_steps)
             }
-            WorkflowStep::PageLoop(sub_steps) => {
+            WorkflowStep::PageLoop(step_args::PageLoopArgs { sub_steps, .. }) => {
                 format!("{self} with sub_steps: {:?}", sub_steps)
             }
-            WorkflowStep::ElementSaveHtmlValue(name, is_inner) => {
+            WorkflowStep::Repeat(step_args::RepeatArgs { times, .. }) => {
+                format!("{self} {times} times")
+            }
+            WorkflowStep::BreakIfMissing(step_args::BreakIfMissingArgs { locator, .. }) => {
+                format!("{self} {locator}")
+            }
+            WorkflowStep::While(step_args::WhileArgs { condition, .. }) => {
+                format!("{self} {condition}")
+            }
+            WorkflowStep::If(step_args::IfArgs { condition, .. }) => {
+                format!("{self} {condition}")
+            }
+            WorkflowStep::Try(step_args::TryArgs { steps, catch, .. }) => {
+                format!(
+                    "{self} with {} steps, {} catch steps",
+                    steps.len(),
+                    catch.len()
+                )
+            }
+            WorkflowStep::ElementSaveHtmlValue(step_args::ElementSaveHtmlValueArgs {
+                name,
+                is_inner,
+                ..
+            }) => {
                 format!("{self} with name={name}, inner={is_inner}")
             }
-            WorkflowStep::PageOpen(url) => format!("{self} {url}"),
+            WorkflowStep::ElementSaveText(step_args::SaveValueArgs { name, .. })
+            | WorkflowStep::PageSaveUrl(step_args::SaveValueArgs { name, .. })
+            | WorkflowStep::PageSaveTitle(step_args::SaveValueArgs { name, .. }) => {
+                format!("{self} with name={name}")
+            }
+            WorkflowStep::ElementSaveAttribute(step_args::ElementSaveAttributeArgs {
+                name,
+                attr: key,
+                ..
+            })
+            | WorkflowStep::ElementSaveProperty(step_args::ElementSavePropertyArgs {
+                name,
+                prop: key,
+                ..
+            })
+            | WorkflowStep::ElementSaveCssValue(step_args::ElementSaveCssValueArgs {
+                name,
+                property: key,
+                ..
+            }) => {
+                format!("{self} {key} with name={name}")
+            }
+            WorkflowStep::PageOpen(step_args::PageOpenArgs { url, .. }) => format!("{self} {url}"),
 
-            WorkflowStep::PageScroll(mode, page_size) => {
+            WorkflowStep::PageScroll(step_args::PageScrollArgs {
+                mode, page_size, ..
+            }) => {
                 format!("{self} in {mode} mode by {page_size} pages")
             }
-            WorkflowStep::PageTakeScreenshot(file_prefix) => {
+            WorkflowStep::PageTakeScreenshot(step_args::TakeScreenshotArgs {
+                file_prefix, ..
+            }) => {
                 format!("{self} with file_prefix={file_prefix}")
             }
-            WorkflowStep::PageWait(duration_ms) => format!("{self} for {duration_ms}ms"),
+            WorkflowStep::PageWait(step_args::PageWaitArgs { duration_ms, .. }) => {
+                format!("{self} for {duration_ms}ms")
+            }
+            WorkflowStep::WaitForElement(step_args::WaitForElementArgs {
+                locator,
+                state,
+                timeout_ms,
+                ..
+            }) => {
+                format!("{self} by {locator} to be {state} within {timeout_ms}ms")
+            }
+            WorkflowStep::WaitForUrl(step_args::WaitForUrlArgs {
+                pattern,
+                timeout_ms,
+                ..
+            }) => {
+                format!("{self} to match {pattern:?} within {timeout_ms}ms")
+            }
+            WorkflowStep::WaitForText(step_args::WaitForTextArgs {
+                text, timeout_ms, ..
+            }) => {
+                format!("{self} {text:?} within {timeout_ms}ms")
+            }
+            WorkflowStep::WaitForJs(step_args::WaitForJsArgs {
+                expression,
+                timeout_ms,
+                ..
+            }) => {
+                format!("{self} `{expression}` within {timeout_ms}ms")
+            }
 
-            WorkflowStep::ElementClick(check_enabled, check_url) => {
+            WorkflowStep::ElementClick(step_args::ElementClickArgs {
+                check_enabled,
+                check_url,
+                ..
+            }) => {
                 format!("{self} with check_enabled={check_enabled}, check_url={check_url}")
             }
-            WorkflowStep::ElementSendKeys(keys) => format!("{self} with keys={keys}"),
+            WorkflowStep::ElementSendKeys(step_args::ElementSendKeysArgs { keys, .. }) => {
+                format!("{self} with keys={keys}")
+            }
 
-            WorkflowStep::ElementTakeScreenshot(file_prefix) => {
+            WorkflowStep::ElementTakeScreenshot(step_args::TakeScreenshotArgs {
+                file_prefix,
+                ..
+            }) => {
                 format!("{self} with file_prefix={file_prefix}")
             }
+            WorkflowStep::AssertElementCount(step_args::AssertElementCountArgs {
+                locator,
+                count,
+                ..
+            }) => {
+                format!("{self} of {locator} to be {count}")
+            }
+            WorkflowStep::AssertText(step_args::AssertTextArgs {
+                expected, locator, ..
+            })
+            | WorkflowStep::AssertAttribute(step_args::AssertAttributeArgs {
+                expected,
+                locator,
+                ..
+            }) => {
+                format!("{self} of {} to be {expected}", describe_target(locator))
+            }
+            WorkflowStep::AssertUrl(step_args::AssertPageArgs { expected, .. })
+            | WorkflowStep::AssertTitle(step_args::AssertPageArgs { expected, .. }) => {
+                format!("{self} to be {expected}")
+            }
+            WorkflowStep::AssertValue(step_args::AssertValueArgs { name, expected, .. }) => {
+                format!("{self} {name} to be {expected}")
+            }
+            WorkflowStep::ExportValues(step_args::ExportValuesArgs {
+                format,
+                file_prefix,
+                ..
+            }) => {
+                format!("{self} as {format} with file_prefix={file_prefix}")
+            }
             // default representation for simple and/or uncovered cases
             _ => format!("{self}"),
+        };
+        match self.on_error() {
+            Some(policy) => format!("{text} (on error: {policy})"),
+            None => text,
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_elements_with_empty_stack() {
+        let current_elements_stack: Vec<Vec<fantoccini::elements::Element>> = Vec::new();
+
+        let result = current_elements(&current_elements_stack, &StepPath(vec![2]));
+
+        match result {
+            Err(SquirrelError::Validation { path, .. }) => assert_eq!(path, StepPath(vec![2])),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn current_element_with_empty_elements() {
+        let current_elements_stack: Vec<Vec<fantoccini::elements::Element>> = vec![Vec::new()];
+
+        let current_elements = current_elements(&current_elements_stack, &StepPath(vec![1]))
+            .expect("Failed to get current elements");
+        let result = current_element(current_elements, &StepPath(vec![1, 0]));
+
+        assert!(matches!(result, Err(SquirrelError::Validation { .. })));
+    }
+
+    #[test]
+    fn loop_breaks_bypass_workflow_policy() {
+        let wf: workflow::Workflow = serde_yaml::from_str(
+            r#"
+              name: "pagination"
+              on_error: continue
+              steps:
+                - !PageLocateElements ["a.next", "first"]
+                - !PageLocateElements {locator: "a.next", mode: "first", on_error: skip_iteration}
+            "#,
+        )
+        .expect("Failed to parse workflow");
+        let not_found = SquirrelError::ElementNotFound {
+            path: StepPath(vec![0]),
+            message: String::from("a.next"),
+        };
+        let breaks = [BreakOn::ElementNotFound];
+
+        assert_eq!(
+            wf.steps[0].error_policy(&not_found, &wf, &[]),
+            &ErrorPolicy::Continue
+        );
+        assert_eq!(
+            wf.steps[0].error_policy(&not_found, &wf, &breaks),
+            &ErrorPolicy::Fail
+        );
+        assert_eq!(
+            wf.steps[1].error_policy(&not_found, &wf, &breaks),
+            &ErrorPolicy::SkipIteration
+        );
+    }
+
+    #[test]
+    fn only_assert_steps_are_assertions() {
+        let steps: Vec<WorkflowStep> = serde_yaml::from_str(
+            r#"
+              - !AssertUrl {expected: "https://example.com/"}
+              - !AssertElementCount ["div.result", 3]
+              - !ElementClick {check_enabled: true}
+            "#,
+        )
+        .expect("Failed to parse steps");
+
+        let assertions: Vec<bool> = steps.iter().map(WorkflowStep::is_assertion).collect();
+
+        assert_eq!(assertions, vec![true, true, false]);
+    }
+}